@@ -2,12 +2,17 @@ use std::time::Instant;
 use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
-use avltree;
 
 pub mod my_rbtree;
 
 fn main() {
-    // 不依赖输入参数：使用固定大小和固定种子，先输出生成的序列
+    // `cargo run --release -- bench-insert`：只跑插入的基准测试
+    if std::env::args().nth(1).as_deref() == Some("bench-insert") {
+        bench_insert();
+        return;
+    }
+
+    // 默认模式不依赖其他输入参数：使用固定大小和固定种子，先输出生成的序列
     let n: usize = 200;
 
     // 固定种子，不从命令行读取
@@ -21,88 +26,107 @@ fn main() {
 
     let mut keys = vec![1, 9, 2, 8, 3, 7, 4, 6, 5];
     // create tree and measure insertion time
-    // Run insertion in a separate thread with a larger stack to avoid stack overflow
+    // 插入不再递归，不需要单独开一个大栈的线程
     let keys_clone = keys.clone();
-    let handle = std::thread::Builder::new()
-        .name("inserter".into())
-        .spawn(move || {
-            let mut rbtree = rbtree::RBTree::new();
-            let mut tree: my_rbtree::RbTree<i32> = my_rbtree::RbTree::new();
-            let mut avl_tree = avltree::SearchTree::new();
+    let mut rbtree = rbtree::RBTree::new();
+    let mut tree: my_rbtree::RbTree<i32> = my_rbtree::RbTree::new();
+    let mut avl_tree = avltree::SearchTree::new();
 
-            let start = Instant::now();
-            for &k in &keys_clone {
-                tree.insert(k);
-            }
-            let elapsed = start.elapsed();
-            println!("Inserted {} keys in {:?} for my RBTree", n, elapsed);
-            
-            let start = Instant::now();
-            for &k in &keys_clone {
-                rbtree.insert(k, k);
-            }
-            let elapsed = start.elapsed();
-            println!("Inserted {} keys in {:?} for RBTree", n, elapsed);
+    let start = Instant::now();
+    for &k in &keys_clone {
+        tree.insert(k);
+    }
+    let elapsed = start.elapsed();
+    println!("Inserted {} keys in {:?} for my RBTree", n, elapsed);
+    
+    let start = Instant::now();
+    for &k in &keys_clone {
+        rbtree.insert(k, k);
+    }
+    let elapsed = start.elapsed();
+    println!("Inserted {} keys in {:?} for RBTree", n, elapsed);
 
-            let start = Instant::now();
-            for &k in &keys_clone {
-                avl_tree.insert(k);
-            }
-            let elapsed = start.elapsed();
-            println!("Inserted {} keys in {:?} for AVLTree", n, elapsed);
+    let start = Instant::now();
+    for &k in &keys_clone {
+        avl_tree.insert(k);
+    }
+    let elapsed = start.elapsed();
+    println!("Inserted {} keys in {:?} for AVLTree", n, elapsed);
 
-            keys.shuffle(&mut rng);
+    keys.shuffle(&mut rng);
 
-            let start = Instant::now();
-            let mut start2;
-            let mut elapsed2;
-            let mut max = Instant::now().elapsed();
-            for &k in &keys_clone {
-                start2 = Instant::now();
-                tree.get(&k);
-                elapsed2 = start2.elapsed();
-                max = elapsed2;
-                if elapsed2 > max {
-                    max = elapsed2;
-                }
-            }
-            let elapsed = start.elapsed();
-            println!("Searched {} keys in {:?} for my RBTree.Max {:?}", n, elapsed, max);
+    let start = Instant::now();
+    let mut start2;
+    let mut elapsed2;
+    let mut max = Instant::now().elapsed();
+    for &k in &keys_clone {
+        start2 = Instant::now();
+        tree.get(&k);
+        elapsed2 = start2.elapsed();
+        max = elapsed2;
+        if elapsed2 > max {
+            max = elapsed2;
+        }
+    }
+    let elapsed = start.elapsed();
+    println!("Searched {} keys in {:?} for my RBTree.Max {:?}", n, elapsed, max);
 
 
-            let start = Instant::now();
-            let mut start2;
-            let mut elapsed2;
-            let mut max = Instant::now().elapsed();
-            for &k in &keys_clone {
-                start2 = Instant::now();
-                rbtree.get(&k);
-                elapsed2 = start2.elapsed();
-                max = elapsed2;
-                if elapsed2 > max {
-                    max = elapsed2;
-                }
-            }
-            let elapsed = start.elapsed();
-            println!("Searched {} keys in {:?} for RBTree.Max {:?}", n, elapsed, max);
+    let start = Instant::now();
+    let mut start2;
+    let mut elapsed2;
+    let mut max = Instant::now().elapsed();
+    for &k in &keys_clone {
+        start2 = Instant::now();
+        rbtree.get(&k);
+        elapsed2 = start2.elapsed();
+        max = elapsed2;
+        if elapsed2 > max {
+            max = elapsed2;
+        }
+    }
+    let elapsed = start.elapsed();
+    println!("Searched {} keys in {:?} for RBTree.Max {:?}", n, elapsed, max);
 
-            let start = Instant::now();
-            let mut start2;
-            let mut elapsed2;
-            let mut max = Instant::now().elapsed();
-            for &k in &keys_clone {
-                start2 = Instant::now();
-                avl_tree.contains(&k);
-                elapsed2 = start2.elapsed();
-                max = elapsed2;
-                if elapsed2 > max {
-                    max = elapsed2;
+    let start = Instant::now();
+    let mut start2;
+    let mut elapsed2;
+    let mut max = Instant::now().elapsed();
+    for &k in &keys_clone {
+        start2 = Instant::now();
+        avl_tree.contains(&k);
+        elapsed2 = start2.elapsed();
+        max = elapsed2;
+        if elapsed2 > max {
+            max = elapsed2;
+        }
+    }
+    let elapsed = start.elapsed();
+    println!("Searched {} keys in {:?} for AVLTree.Max {:?}", n, elapsed, max);
+}
+
+// 插入的基准测试：固定种子的随机序列和有序序列，每组取5次中最快的一次
+// 只用到公开的insert_multi，把这个函数复制到旧版本上即可得到可以直接对比的数字
+fn bench_insert() {
+    let mut rng = StdRng::seed_from_u64(0x12345678);
+    for n in [100_000usize, 1_000_000] {
+        let sorted: Vec<i32> = (0..n as i32).collect();
+        let mut random = sorted.clone();
+        random.shuffle(&mut rng);
+        for (name, keys) in [("random", &random), ("sorted", &sorted)] {
+            let mut best = None;
+            for _ in 0..5 {
+                let start = Instant::now();
+                let mut tree: my_rbtree::RbTree<i32> = my_rbtree::RbTree::new();
+                for &k in keys {
+                    tree.insert_multi(k);
                 }
+                let elapsed = start.elapsed();
+                assert_eq!(tree.len(), n);
+                drop(tree);
+                best = Some(best.map_or(elapsed, |b: std::time::Duration| b.min(elapsed)));
             }
-            let elapsed = start.elapsed();
-            println!("Searched {} keys in {:?} for AVLTree.Max {:?}", n, elapsed, max);
-        })
-        .expect("failed to spawn inserter thread");
-
-    handle.join().expect("inserter thread panicked");
+            println!("{n:>8} {name}: {:?}", best.unwrap());
+        }
+    }
 }
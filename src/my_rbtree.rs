@@ -1,4 +1,10 @@
-use std::{fmt::Display, ptr::NonNull, rc::Weak};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+};
 
 #[derive(Clone, Copy)]
 enum RbColor {
@@ -15,17 +21,25 @@ impl PartialEq for RbColor {
     }
 }
 
-struct RbNode<T: Ord> {
+// 节点由Box::into_raw分配，之后只通过NonNull裸指针访问
+// 别名规则：
+// 1. 修改树结构时不创建指向节点或节点字段的&mut引用，需要指向子节点所在位置时用`&raw mut`取裸指针，
+//    这样同一节点的其他裸指针在此期间仍然有效（可以用Miri检查）
+// 2. 返回给调用者的&T/&mut T借用自&self/&mut self，借用期间树的结构不会改变
+// 3. 节点只在remove_node、clear和IntoIter中用Box::from_raw释放，释放前已从树中摘下
+struct RbNode<T, A: Augment<T>> {
     value: T,
+    summary: A::Summary,
     color: RbColor,
-    left: Option<NonNull<RbNode<T>>>,
-    right: Option<NonNull<RbNode<T>>>,
-    parent: Option<NonNull<RbNode<T>>>,
+    left: Option<NonNull<RbNode<T, A>>>,
+    right: Option<NonNull<RbNode<T, A>>>,
+    parent: Option<NonNull<RbNode<T, A>>>,
 }
 
-impl<T: Ord> RbNode<T> {
+impl<T, A: Augment<T>> RbNode<T, A> {
     fn new(value: T) -> Self {
         RbNode {
+            summary: A::combine(None, &value, None),
             value,
             color: RbColor::Red,
             left: None,
@@ -35,31 +49,124 @@ impl<T: Ord> RbNode<T> {
     }
 }
 
-pub struct RbTree<T: Ord> {
-    root: Option<NonNull<RbNode<T>>>,
+mod augment;
+mod build;
+mod compare;
+mod cursor;
+pub mod interval;
+mod iter;
+mod join;
+pub mod map;
+mod set_ops;
+mod validate;
+
+use iter::RawRange;
+
+pub use augment::{Augment, SubtreeSize};
+pub use compare::{Comparator, NaturalOrder};
+pub use cursor::{Cursor, CursorMut, UnorderedKeyError};
+pub use interval::IntervalTree;
+pub use iter::{ExtractIf, IntoIter, Iter, Range};
+pub use map::{Entry, OccupiedEntry, RbTreeMap, VacantEntry};
+pub use set_ops::{Difference, Intersection, SymmetricDifference, Union};
+pub use validate::InvariantError;
+
+#[cfg(test)]
+mod tests;
+
+// 查找失败时新节点应当挂入的位置，parent为None时作为根节点，left表示挂在parent的哪一侧
+struct InsertPos<T, A: Augment<T>> {
+    parent: Option<NonNull<RbNode<T, A>>>,
+    left: bool,
+}
+
+/// 红黑树，A为每个节点维护的子树摘要，C为比较器
+///
+/// 相等元素的处理由插入方法决定：`insert`和`replace`把树当作集合使用，
+/// `insert_multi`把树当作多重集合使用，相等的元素按插入的先后顺序排列
+pub struct RbTree<T, A: Augment<T> = (), C = NaturalOrder> {
+    root: Option<NonNull<RbNode<T, A>>>,
     size: usize,
+    cmp: C,
+}
+
+impl<T, A: Augment<T>, C: Default> Default for RbTree<T, A, C> {
+    fn default() -> Self {
+        Self::with_augment_and_comparator_inner(C::default())
+    }
 }
 
 impl<T: Ord> RbTree<T> {
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, C: Comparator<T>> RbTree<T, (), C> {
+    /// 创建按cmp排序的空树，例如`RbTree::with_comparator(|a: &u64, b: &u64| b.cmp(a))`
+    pub fn with_comparator(cmp: C) -> Self {
+        Self::with_augment_and_comparator_inner(cmp)
+    }
+}
+
+impl<T, A: Augment<T>> RbTree<T, A> {
+    /// 创建维护摘要A的空树，例如`RbTree::with_augment(SubtreeSize)`
+    pub fn with_augment(_augment: A) -> Self {
+        Self::default()
+    }
+}
+
+impl<T, A: Augment<T>, C> RbTree<T, A, C> {
+    /// 同时指定摘要和比较器
+    pub fn with_augment_and_comparator(_augment: A, cmp: C) -> Self {
+        Self::with_augment_and_comparator_inner(cmp)
+    }
+
+    fn with_augment_and_comparator_inner(cmp: C) -> Self {
         RbTree {
             root: None,
             size: 0,
+            cmp,
+        }
+    }
+
+    // 根据子节点重新计算节点的摘要
+    fn update_summary(node: NonNull<RbNode<T, A>>) {
+        // 零大小的摘要不携带任何信息，不需要维护
+        if size_of::<A::Summary>() == 0 {
+            return;
+        }
+        let node_ptr = node.as_ptr();
+        unsafe {
+            let left = (*node_ptr).left.map(|l| &(*l.as_ptr()).summary);
+            let right = (*node_ptr).right.map(|r| &(*r.as_ptr()).summary);
+            (*node_ptr).summary = A::combine(left, &(*node_ptr).value, right);
+        }
+    }
+
+    // 从node开始沿父指针向上，直到根节点，依次重新计算摘要
+    fn update_summary_to_root(node: Option<NonNull<RbNode<T, A>>>) {
+        if size_of::<A::Summary>() == 0 {
+            return;
+        }
+        let mut current = node;
+        while let Some(n) = current {
+            Self::update_summary(n);
+            current = unsafe { (*n.as_ptr()).parent };
         }
     }
 
     // 向上修复成2-3-4树
     fn insert_fixup(
-        cur_node: NonNull<RbNode<T>>,
-        parent_ref: &mut Option<NonNull<RbNode<T>>>,
-        uncle_ref: &mut Option<NonNull<RbNode<T>>>,
-        gp_ref: &mut Option<NonNull<RbNode<T>>>,
-    ) -> Option<NonNull<RbNode<T>>> {
-        let parent_ptr = (parent_ref.unwrap()).as_ptr();
-        let parent_value = unsafe { &(*parent_ptr).value };
+        cur_node: NonNull<RbNode<T, A>>,
+        parent_ref: *mut Option<NonNull<RbNode<T, A>>>,
+        uncle_ref: *mut Option<NonNull<RbNode<T, A>>>,
+        gp_ref: *mut Option<NonNull<RbNode<T, A>>>,
+    ) -> Option<NonNull<RbNode<T, A>>> {
+        let parent = unsafe { *parent_ref }.unwrap();
+        let parent_ptr = parent.as_ptr();
         let parent_color = unsafe { (*parent_ptr).color };
         let current_color = unsafe { (*cur_node.as_ptr()).color };
-        let current_value = unsafe { &(*cur_node.as_ptr()).value };
         let parent_left = unsafe { (*parent_ptr).left };
         let parent_right = unsafe { (*parent_ptr).right };
         // 不需要继续向上合并的两种情况
@@ -75,9 +182,7 @@ impl<T: Ord> RbTree<T> {
             let gp_ptr = gp.as_ptr();
             // 由于根节点始终是黑色，所以不可能出现连续红节点到达根节点的情况
 
-            let gp_value = unsafe { &(*gp.as_ptr()).value };
-
-            let (uncle_ptr, uncle_color) = match uncle_ref {
+            let (uncle_ptr, uncle_color) = match unsafe { *uncle_ref } {
                 Some(uncle) => {
                     let ptr = uncle.as_ptr();
                     (ptr, unsafe { (*ptr).color })
@@ -126,9 +231,12 @@ impl<T: Ord> RbTree<T> {
                 return Some(gp);
             } else {
                 // 是4-节点，重新排序
-                if current_value < parent_value {
+                // 用指针判断方向，相等的值经过旋转后也可能出现在左子树
+                let cur_is_left = parent_left == Some(cur_node);
+                let parent_is_left = unsafe { (*gp_ptr).left == *parent_ref };
+                if cur_is_left {
                     // 左倾
-                    if parent_value < gp_value {
+                    if parent_is_left {
                         // 2-3-4树
                         //       .          .
                         //       |          |
@@ -154,7 +262,12 @@ impl<T: Ord> RbTree<T> {
 
                             (*gp_ptr).left = parent_right;
                             (*parent_ptr).right = Some(gp);
+                            if let Some(b) = parent_right {
+                                (*b.as_ptr()).parent = Some(gp);
+                            }
                         }
+                        Self::update_summary(gp);
+                        Self::update_summary(parent);
                     } else {
                         // 2-3-4树
                         //   .              .
@@ -177,6 +290,12 @@ impl<T: Ord> RbTree<T> {
 
                             (*parent_ptr).left = (*cur_ptr).right;
                             (*gp_ptr).right = (*cur_ptr).left;
+                            if let Some(b) = (*cur_ptr).right {
+                                (*b.as_ptr()).parent = Some(parent);
+                            }
+                            if let Some(a) = (*cur_ptr).left {
+                                (*a.as_ptr()).parent = Some(gp);
+                            }
 
                             (*cur_ptr).left = Some(gp);
                             (*cur_ptr).right = (*cur_ptr).parent;
@@ -187,9 +306,12 @@ impl<T: Ord> RbTree<T> {
 
                             *gp_ref = Some(cur_node);
                         }
+                        Self::update_summary(gp);
+                        Self::update_summary(parent);
+                        Self::update_summary(cur_node);
                     }
                 } else {
-                    if parent_value < gp_value {
+                    if parent_is_left {
                         // 2-3-4树
                         //       .          .
                         //       |          |
@@ -211,6 +333,12 @@ impl<T: Ord> RbTree<T> {
 
                             (*parent_ptr).right = (*cur_ptr).left;
                             (*gp_ptr).left = (*cur_ptr).right;
+                            if let Some(a) = (*cur_ptr).left {
+                                (*a.as_ptr()).parent = Some(parent);
+                            }
+                            if let Some(b) = (*cur_ptr).right {
+                                (*b.as_ptr()).parent = Some(gp);
+                            }
 
                             (*cur_ptr).left = (*cur_ptr).parent;
                             (*cur_ptr).right = (*parent_ptr).parent;
@@ -222,6 +350,9 @@ impl<T: Ord> RbTree<T> {
 
                             (*gp_ref) = Some(cur_node);
                         }
+                        Self::update_summary(parent);
+                        Self::update_summary(gp);
+                        Self::update_summary(cur_node);
                     } else {
                         // 2-3-4树
                         //   .              .
@@ -248,7 +379,12 @@ impl<T: Ord> RbTree<T> {
 
                             (*gp_ptr).right = parent_left;
                             (*parent_ptr).left = Some(gp);
+                            if let Some(b) = parent_left {
+                                (*b.as_ptr()).parent = Some(gp);
+                            }
                         }
+                        Self::update_summary(gp);
+                        Self::update_summary(parent);
                     }
                 }
             }
@@ -256,88 +392,82 @@ impl<T: Ord> RbTree<T> {
         None
     }
 
-    // 先按二叉树的方式插入，不管平衡
-    fn insert_new(
-        new_node: NonNull<RbNode<T>>,
-        parent_ref: &mut Option<NonNull<RbNode<T>>>,
-        uncle_ref: &mut Option<NonNull<RbNode<T>>>,
-        grand_parent_ref: &mut Option<NonNull<RbNode<T>>>,
-    ) -> Option<NonNull<RbNode<T>>> {
-        let new_value = unsafe { &(*new_node.as_ptr()).value };
-        let parent_ptr = (parent_ref.unwrap()).as_ptr();
-        let parent_value = unsafe { &(*parent_ptr).value };
-        let parent_left = unsafe { &mut (*parent_ptr).left };
-        let parent_right = unsafe { &mut (*parent_ptr).right };
-
-        let check = if new_value < parent_value {
-            if parent_left.is_some() {
-                Self::insert_new(new_node, parent_left, parent_right, parent_ref)
-            } else {
-                // 左为空，直接插入
-                unsafe {
-                    (*parent_ptr).left = Some(new_node);
-                    (*new_node.as_ptr()).parent = Some(NonNull::new_unchecked(parent_ptr));
-                }
-                Self::insert_fixup(new_node, parent_ref, uncle_ref, grand_parent_ref)
+    /// 按集合的方式插入，已有相等的元素时不做修改并返回false
+    pub fn insert(&mut self, value: T) -> bool
+    where
+        C: Comparator<T>,
+    {
+        match self.search_by(|v| self.cmp.compare(&value, v)) {
+            Ok(_) => false,
+            Err(pos) => {
+                self.insert_at(value, pos);
+                true
             }
-        } else {
-            if parent_right.is_some() {
-                Self::insert_new(new_node, parent_right, parent_left, parent_ref)
-            } else {
-                // 右为空，直接插入
-                unsafe {
-                    (*parent_ptr).right = Some(new_node);
-                    (*new_node.as_ptr()).parent = Some(NonNull::new_unchecked(parent_ptr));
-                }
-                Self::insert_fixup(new_node, parent_ref, uncle_ref, grand_parent_ref)
+        }
+    }
+
+    /// 按集合的方式插入，已有相等的元素时用value替换它并返回旧元素
+    pub fn replace(&mut self, value: T) -> Option<T>
+    where
+        C: Comparator<T>,
+    {
+        match self.search_by(|v| self.cmp.compare(&value, v)) {
+            Ok(node) => {
+                let old = std::mem::replace(unsafe { &mut (*node.as_ptr()).value }, value);
+                // 相等的元素也可能有不同的摘要
+                Self::update_summary_to_root(Some(node));
+                Some(old)
+            }
+            Err(pos) => {
+                self.insert_at(value, pos);
+                None
             }
-        };
-        if let Some(check_node) = check {
-            let _parent_ptr = match unsafe { (*check_node.as_ptr()).parent } {
-                Some(ptr) => ptr.as_ptr(),
-                None => {
-                    return None;
+        }
+    }
+
+    /// 按多重集合的方式插入，总是插入新节点，排在所有相等的元素之后
+    pub fn insert_multi(&mut self, value: T)
+    where
+        C: Comparator<T>,
+    {
+        // 从根向下找到空位，相等的元素向右走，新元素排在它们之后
+        let mut parent = None;
+        let mut left = false;
+        let mut current = self.root;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            parent = Some(node);
+            left = self.cmp.compare(&value, unsafe { &(*node_ptr).value }) == Ordering::Less;
+            current = unsafe {
+                if left {
+                    (*node_ptr).left
+                } else {
+                    (*node_ptr).right
                 }
             };
-            if _parent_ptr == parent_ptr {
-                unsafe {
-                    if let Some(left) = (*parent_ptr).left {
-                        if left == check_node {
-                            return Self::insert_fixup(
-                                left,
-                                parent_ref,
-                                uncle_ref,
-                                grand_parent_ref,
-                            );
-                        }
-                    }
-                    if let Some(right) = (*parent_ptr).right {
-                        if right == check_node {
-                            return Self::insert_fixup(
-                                right,
-                                parent_ref,
-                                uncle_ref,
-                                grand_parent_ref,
-                            );
-                        }
-                    }
-                }
-            } else {
-                return check;
-            }
         }
-        None
+        self.insert_at(value, InsertPos { parent, left });
     }
 
-    pub fn insert(&mut self, key: T) {
-        let new_node = RbNode::new(key);
-        let new_node = Box::new(new_node);
-        let new_node_raw = Box::into_raw(new_node);
-        let new_node = unsafe { NonNull::new_unchecked(new_node_raw) };
+    // 在查找得到的空位上挂入新值
+    // 调用者要保证这个位置符合排序，挂入后直接修复，不再从根重新查找
+    fn insert_at(&mut self, value: T, pos: InsertPos<T, A>) -> NonNull<RbNode<T, A>> {
+        let InsertPos { parent, left } = pos;
+        let new_node = Box::into_raw(Box::new(RbNode::new(value)));
+        let new_node = unsafe { NonNull::new_unchecked(new_node) };
 
-        match self.root {
-            Some(_) => {
-                Self::insert_new(new_node, &mut self.root, &mut None, &mut None);
+        match parent {
+            Some(p) => {
+                unsafe {
+                    if left {
+                        (*p.as_ptr()).left = Some(new_node);
+                    } else {
+                        (*p.as_ptr()).right = Some(new_node);
+                    }
+                    (*new_node.as_ptr()).parent = Some(p);
+                }
+                Self::update_summary_to_root(parent);
+                Self::insert_fixup_from(&raw mut self.root, new_node);
             }
             None => {
                 self.root = Some(new_node);
@@ -347,13 +477,51 @@ impl<T: Ord> RbTree<T> {
             (*self.root.unwrap_unchecked().as_ptr()).color = RbColor::Black;
         }
         self.size += 1;
+        self.paranoid_check();
+        new_node
+    }
+
+    // 从刚挂入的节点开始向上修复
+    // 每一层都根据父指针重新找出父节点、叔叔节点和祖父节点所在的位置，再交给insert_fixup
+    // root是这棵（子）树根节点所在的位置，join时修复的是还没有放进RbTree的子树
+    fn insert_fixup_from(root: *mut Option<NonNull<RbNode<T, A>>>, node: NonNull<RbNode<T, A>>) {
+        let mut current = node;
+        while let Some(parent) = unsafe { (*current.as_ptr()).parent } {
+            // 父节点是根节点时一定是黑色，不需要修复
+            let Some(gp) = (unsafe { (*parent.as_ptr()).parent }) else {
+                break;
+            };
+            let gp_ptr = gp.as_ptr();
+            let (parent_ref, uncle_ref) = unsafe {
+                if (*gp_ptr).left == Some(parent) {
+                    (&raw mut (*gp_ptr).left, &raw mut (*gp_ptr).right)
+                } else {
+                    (&raw mut (*gp_ptr).right, &raw mut (*gp_ptr).left)
+                }
+            };
+            let gp_ref = match unsafe { (*gp_ptr).parent } {
+                Some(ggp) => unsafe {
+                    let ggp_ptr = ggp.as_ptr();
+                    if (*ggp_ptr).left == Some(gp) {
+                        &raw mut (*ggp_ptr).left
+                    } else {
+                        &raw mut (*ggp_ptr).right
+                    }
+                },
+                None => root,
+            };
+            match Self::insert_fixup(current, parent_ref, uncle_ref, gp_ref) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
     }
 
     fn change_child(
         &mut self,
-        parent: Option<NonNull<RbNode<T>>>,
-        old_child: NonNull<RbNode<T>>,
-        new_child: Option<NonNull<RbNode<T>>>,
+        parent: Option<NonNull<RbNode<T, A>>>,
+        old_child: NonNull<RbNode<T, A>>,
+        new_child: Option<NonNull<RbNode<T, A>>>,
     ) {
         if let Some(parent) = parent {
             let parent_ptr = parent.as_ptr();
@@ -387,7 +555,7 @@ impl<T: Ord> RbTree<T> {
     }
 
     // 从右子树中找到最左节点
-    fn find_left_node_right(&self, right: NonNull<RbNode<T>>) -> NonNull<RbNode<T>> {
+    fn find_left_node_right(right: NonNull<RbNode<T, A>>) -> NonNull<RbNode<T, A>> {
         let mut current = right;
         unsafe {
             while let Some(left) = (*current.as_ptr()).left {
@@ -397,7 +565,57 @@ impl<T: Ord> RbTree<T> {
         current
     }
 
-    fn delete_fixup(&mut self, parent: NonNull<RbNode<T>>) {
+    // 从左子树中找到最右节点
+    fn find_right_node_left(left: NonNull<RbNode<T, A>>) -> NonNull<RbNode<T, A>> {
+        let mut current = left;
+        unsafe {
+            while let Some(right) = (*current.as_ptr()).right {
+                current = right;
+            }
+        }
+        current
+    }
+
+    // 中序遍历的下一个节点
+    // 有右子树时是右子树的最左节点，否则沿父指针向上，直到从左侧回到某个祖先
+    fn next_node(node: NonNull<RbNode<T, A>>) -> Option<NonNull<RbNode<T, A>>> {
+        unsafe {
+            if let Some(right) = (*node.as_ptr()).right {
+                return Some(Self::find_left_node_right(right));
+            }
+            let mut child = node;
+            let mut parent = (*node.as_ptr()).parent;
+            while let Some(p) = parent {
+                if (*p.as_ptr()).left == Some(child) {
+                    return Some(p);
+                }
+                child = p;
+                parent = (*p.as_ptr()).parent;
+            }
+        }
+        None
+    }
+
+    // 中序遍历的上一个节点，与next_node对称
+    fn prev_node(node: NonNull<RbNode<T, A>>) -> Option<NonNull<RbNode<T, A>>> {
+        unsafe {
+            if let Some(left) = (*node.as_ptr()).left {
+                return Some(Self::find_right_node_left(left));
+            }
+            let mut child = node;
+            let mut parent = (*node.as_ptr()).parent;
+            while let Some(p) = parent {
+                if (*p.as_ptr()).right == Some(child) {
+                    return Some(p);
+                }
+                child = p;
+                parent = (*p.as_ptr()).parent;
+            }
+        }
+        None
+    }
+
+    fn delete_fixup(&mut self, parent: NonNull<RbNode<T, A>>) {
         let mut parent = parent;
         let mut sibling;
         let mut node = None;
@@ -445,6 +663,8 @@ impl<T: Ord> RbTree<T> {
                             let right_ptr = right.as_ptr();
                             (*right_ptr).parent = Some(parent);
                         }
+                        Self::update_summary(parent);
+                        Self::update_summary(NonNull::new_unchecked(sibling_ptr));
 
                         sibling = (*parent_ptr).right;
                     }
@@ -537,7 +757,13 @@ impl<T: Ord> RbTree<T> {
 
                             (*parent_ptr).parent = Some(sl);
                             (*sibling_ptr).parent = Some(sl);
-                            (*sl_ptr).color = RbColor::Black;
+                            // 新的子树根继承原父节点的颜色
+                            (*sl_ptr).color = (*parent_ptr).color;
+                            (*parent_ptr).color = RbColor::Black;
+
+                            Self::update_summary(parent);
+                            Self::update_summary(NonNull::new_unchecked(sibling_ptr));
+                            Self::update_summary(sl);
                             break;
                         }
                         (RbColor::Black, RbColor::Red) | (RbColor::Red, RbColor::Red) => {
@@ -585,6 +811,12 @@ impl<T: Ord> RbTree<T> {
                             
                             (*sr_ptr).parent = sibling;
                             (*sr_ptr).color = RbColor::Black;
+                            // 兄弟节点成为新的子树根，继承原父节点的颜色
+                            (*sibling_ptr).color = (*parent_ptr).color;
+                            (*parent_ptr).color = RbColor::Black;
+
+                            Self::update_summary(parent);
+                            Self::update_summary(NonNull::new_unchecked(sibling_ptr));
                             break;
                         }
                     };
@@ -621,6 +853,8 @@ impl<T: Ord> RbTree<T> {
                             let left_ptr = left.as_ptr();
                             (*left_ptr).parent = Some(parent);
                         }
+                        Self::update_summary(parent);
+                        Self::update_summary(NonNull::new_unchecked(sibling_ptr));
 
                         sibling = (*parent_ptr).left;
                     }
@@ -712,7 +946,13 @@ impl<T: Ord> RbTree<T> {
                             }
                             (*parent_ptr).parent = Some(sr);
                             (*sibling_ptr).parent = Some(sr);
-                            (*sr_ptr).color = RbColor::Black;
+                            // 新的子树根继承原父节点的颜色
+                            (*sr_ptr).color = (*parent_ptr).color;
+                            (*parent_ptr).color = RbColor::Black;
+
+                            Self::update_summary(parent);
+                            Self::update_summary(NonNull::new_unchecked(sibling_ptr));
+                            Self::update_summary(sr);
                             break;
                         }
                         (RbColor::Red, RbColor::Black) | (RbColor::Red, RbColor::Red) => {
@@ -761,6 +1001,12 @@ impl<T: Ord> RbTree<T> {
 
                             (*sl_ptr).parent = sibling;
                             (*sl_ptr).color = RbColor::Black;
+                            // 兄弟节点成为新的子树根，继承原父节点的颜色
+                            (*sibling_ptr).color = (*parent_ptr).color;
+                            (*parent_ptr).color = RbColor::Black;
+
+                            Self::update_summary(parent);
+                            Self::update_summary(NonNull::new_unchecked(sibling_ptr));
                             break;
                         }
                     };
@@ -769,9 +1015,11 @@ impl<T: Ord> RbTree<T> {
         }
     }
 
-    fn delete_node(&mut self, node_ptr: *mut RbNode<T>) {
+    fn delete_node(&mut self, node_ptr: *mut RbNode<T, A>) {
         let mut rebalance = None;
         let parent = unsafe { (*node_ptr).parent };
+        // 结构发生变化的最低位置，从这里向上更新摘要
+        let mut update_from = parent;
 
         let (left, right) = unsafe { ((*node_ptr).left, (*node_ptr).right) };
 
@@ -782,12 +1030,11 @@ impl<T: Ord> RbTree<T> {
                 let child_ptr = right_ptr;
 
                 // 选择中继节点
-                let successor_ptr;
                 let parent_ptr;
                 let successor_right;
-                let (child_left, child_right) = unsafe { ((*child_ptr).left, (*child_ptr).right) };
+                let child_right = unsafe { (*child_ptr).right };
 
-                successor_ptr = self.find_left_node_right(right_node).as_ptr();
+                let successor_ptr = Self::find_left_node_right(right_node).as_ptr();
                 if successor_ptr == right_ptr {
                     parent_ptr = child_ptr;
                     successor_right = child_right;
@@ -832,6 +1079,12 @@ impl<T: Ord> RbTree<T> {
                         }
                     }
                 }
+
+                // 后继节点顶替被删除节点的位置，也要继承它的颜色
+                unsafe {
+                    (*successor_ptr).color = (*node_ptr).color;
+                    update_from = Some(NonNull::new_unchecked(parent_ptr));
+                }
             }
             (None, Some(right_node)) => {
                 unsafe {
@@ -857,68 +1110,430 @@ impl<T: Ord> RbTree<T> {
             }
         }
 
+        Self::update_summary_to_root(update_from);
+
         if let Some(rebalance_node) = rebalance {
             self.delete_fixup(rebalance_node);
         }
     }
 
-    pub fn delete(&mut self, key: &T) {
-        let mut node = &self.root;
-        while let Some(n) = node {
-            let node_ptr = n.as_ptr();
-            let node_value = unsafe { &(*node_ptr).value };
-            if key == node_value {
-                // 找到节点，删除
-                self.delete_node(node_ptr);
-                let _ = unsafe { Box::from_raw(node_ptr) };
-                return;
-            } else if key < node_value {
-                node = unsafe { &(*node_ptr).left };
-            } else {
-                node = unsafe { &(*node_ptr).right };
+    /// 删除与key相等的元素，key可以是元素借出的类型，例如`RbTree<String>`可以用`&str`删除
+    ///
+    /// 有多个相等的元素时删除中序上的第一个，与`remove_one`相同，返回是否删除了元素
+    pub fn delete<Q>(&mut self, key: &Q) -> bool
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.remove(key).is_some()
+    }
+
+    /// 删除与key相等的元素并取回它，有多个相等的元素时取中序上的第一个
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        let node = self.first_equal(key)?;
+        Some(self.remove_node(node))
+    }
+
+    /// 与`remove`相同，和`BTreeSet::take`同名
+    pub fn take<Q>(&mut self, key: &Q) -> Option<T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.remove(key)
+    }
+
+    // 中序上第一个与key相等的节点
+    fn first_equal<Q>(&self, key: &Q) -> Option<NonNull<RbNode<T, A>>>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        let node = self.first_node_by(|v| self.cmp.compare(v.borrow(), key) != Ordering::Less)?;
+        let value = unsafe { (*node.as_ptr()).value.borrow() };
+        (self.cmp.compare(value, key) == Ordering::Equal).then_some(node)
+    }
+
+    // 按给定的比较函数查找节点，f返回要找的键相对节点值的大小
+    fn find_by<F>(&self, f: F) -> Option<NonNull<RbNode<T, A>>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        self.search_by(f).ok()
+    }
+
+    // 与find_by相同，找不到时返回可以挂入新节点的位置，交给insert_at使用
+    fn search_by<F>(&self, mut f: F) -> Result<NonNull<RbNode<T, A>>, InsertPos<T, A>>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let mut parent = None;
+        let mut left = false;
+        let mut current = self.root;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            parent = Some(node);
+            match f(unsafe { &(*node_ptr).value }) {
+                Ordering::Equal => return Ok(node),
+                Ordering::Less => {
+                    left = true;
+                    current = unsafe { (*node_ptr).left };
+                }
+                Ordering::Greater => {
+                    left = false;
+                    current = unsafe { (*node_ptr).right };
+                }
             }
         }
+        Err(InsertPos { parent, left })
     }
 
-    pub fn get(&self, key: &T) -> Option<&T> {
+    // 从树中摘下节点，释放节点并取回其中的值
+    fn remove_node(&mut self, node: NonNull<RbNode<T, A>>) -> T {
+        self.delete_node(node.as_ptr());
+        self.size -= 1;
+        self.paranoid_check();
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        node.value
+    }
+
+    // 找到中序第一个使pred为真的节点，pred在中序上须是先假后真
+    fn first_node_by<F>(&self, mut pred: F) -> Option<NonNull<RbNode<T, A>>>
+    where
+        F: FnMut(&T) -> bool,
+    {
         let mut current = self.root;
+        let mut result = None;
         while let Some(node) = current {
             let node_ptr = node.as_ptr();
-            let node_value = unsafe { &(*node_ptr).value };
-            if key == node_value {
-                return Some(node_value);
-            } else if key < node_value {
+            if pred(unsafe { &(*node_ptr).value }) {
+                result = Some(node);
                 current = unsafe { (*node_ptr).left };
             } else {
                 current = unsafe { (*node_ptr).right };
             }
         }
-        None
+        result
+    }
+
+    // 找到中序最后一个使pred为真的节点，pred在中序上须是先真后假
+    fn last_node_by<F>(&self, mut pred: F) -> Option<NonNull<RbNode<T, A>>>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut current = self.root;
+        let mut result = None;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            if pred(unsafe { &(*node_ptr).value }) {
+                result = Some(node);
+                current = unsafe { (*node_ptr).right };
+            } else {
+                current = unsafe { (*node_ptr).left };
+            }
+        }
+        result
+    }
+
+    // 找到区间两端的节点，key从节点值中取出参与比较的部分，cmp比较这一部分
+    fn range_nodes_by<Q, R, F, G>(&self, range: &R, key: F, cmp: G) -> RawRange<T, A>
+    where
+        Q: ?Sized,
+        R: RangeBounds<Q>,
+        F: Fn(&T) -> &Q,
+        G: Fn(&Q, &Q) -> Ordering,
+    {
+        let (start, end) = (range.start_bound(), range.end_bound());
+        check_range(start, end, &cmp);
+
+        let front = self.first_node_by(|v| after_start(key(v), start, &cmp));
+        let back = self.last_node_by(|v| before_end(key(v), end, &cmp));
+        match (front, back) {
+            // 区间内没有节点时，两端会交错
+            (Some(f), Some(b))
+                if unsafe { cmp(key(&(*f.as_ptr()).value), key(&(*b.as_ptr()).value)) }
+                    != Ordering::Greater =>
+            {
+                RawRange::new(front, back)
+            }
+            _ => RawRange::new(None, None),
+        }
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T, A>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+        C: Comparator<Q>,
+    {
+        Range::new(self.range_nodes_by(&range, |v| v.borrow(), |a, b| self.cmp.compare(a, b)))
+    }
+
+    /// 大于等于key的最小元素
+    pub fn ceiling<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.first_node_by(|v| self.cmp.compare(v.borrow(), key) != Ordering::Less)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 小于等于key的最大元素
+    pub fn floor<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.last_node_by(|v| self.cmp.compare(v.borrow(), key) != Ordering::Greater)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 严格大于key的最小元素
+    pub fn upper_bound<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.first_node_by(|v| self.cmp.compare(v.borrow(), key) == Ordering::Greater)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 严格小于key的最大元素
+    pub fn lower_bound<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.last_node_by(|v| self.cmp.compare(v.borrow(), key) == Ordering::Less)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 查找与key相等的元素，key的类型规则与`delete`相同，有多个时返回中序上的第一个
+    pub fn get<Q>(&self, key: &Q) -> Option<&T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.first_equal(key)
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 与key相等的元素个数
+    pub fn count<Q>(&self, key: &Q) -> usize
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.equal_range(key).count()
+    }
+
+    /// 所有与key相等的元素，按插入的先后顺序排列
+    pub fn equal_range<Q>(&self, key: &Q) -> Range<'_, T, A>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.range((Bound::Included(key), Bound::Included(key)))
+    }
+
+    /// 删除中序上第一个与key相等的元素并返回，即最早插入的一个
+    pub fn remove_one<Q>(&mut self, key: &Q) -> Option<T>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.remove(key)
+    }
+
+    /// 删除所有与key相等的元素，返回删除的个数
+    pub fn remove_all<Q>(&mut self, key: &Q) -> usize
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        let mut removed = 0;
+        while self.remove_one(key).is_some() {
+            removed += 1;
+        }
+        removed
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    /// 元素个数，多重集合中相等的元素分别计数
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// 最小的元素，有多个相等的最小元素时返回最早插入的一个
+    pub fn first(&self) -> Option<&T> {
+        let node = Self::find_left_node_right(self.root?);
+        Some(unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 最大的元素，有多个相等的最大元素时返回最晚插入的一个
+    pub fn last(&self) -> Option<&T> {
+        let node = Self::find_right_node_left(self.root?);
+        Some(unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 删除并取回最小的元素
+    pub fn pop_first(&mut self) -> Option<T> {
+        let node = Self::find_left_node_right(self.root?);
+        Some(self.remove_node(node))
+    }
+
+    /// 删除并取回最大的元素
+    pub fn pop_last(&mut self) -> Option<T> {
+        let node = Self::find_right_node_left(self.root?);
+        Some(self.remove_node(node))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        Iter::new(
+            self.root.map(Self::find_left_node_right),
+            self.root.map(Self::find_right_node_left),
+            self.size,
+        )
     }
 
     pub fn enumerate(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        fn inorder<T: Ord>(node: Option<NonNull<RbNode<T>>>, result: &mut Vec<&T>) {
-            if let Some(n) = node {
-                unsafe {
-                    inorder((*n.as_ptr()).left, result);
-                    result.push(&(*n.as_ptr()).value);
-                    inorder((*n.as_ptr()).right, result);
+        self.iter().collect()
+    }
+
+    /// 只保留使f为真的元素，按从小到大的顺序对每个元素调用一次f
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.extract_if(|v| !f(v)).for_each(drop);
+    }
+
+    /// 惰性地删除并取出使pred为真的元素
+    ///
+    /// 在一次中序遍历中边走边删，每次删除后树都满足红黑树的性质，
+    /// 迭代器提前丢弃时还没检查的元素都留在树中
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, A, C, F>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        ExtractIf::new(self, pred)
+    }
+
+    /// 释放所有节点，树变为空树
+    ///
+    /// 不断右旋把左子树提上来，当前节点没有左子树时释放它再处理右子树，
+    /// 不需要递归，再深的树也不会栈溢出
+    pub fn clear(&mut self) {
+        let mut current = self.root.take();
+        self.size = 0;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            unsafe {
+                match (*node_ptr).left {
+                    Some(left) => {
+                        (*node_ptr).left = (*left.as_ptr()).right;
+                        (*left.as_ptr()).right = Some(node);
+                        current = Some(left);
+                    }
+                    None => {
+                        current = (*node_ptr).right;
+                        drop(Box::from_raw(node_ptr));
+                    }
                 }
             }
         }
-        inorder(self.root, &mut result);
-        result
     }
 }
 
-impl<T: Ord + Display + Default + Clone + Display> Display for RbTree<T> {
+impl<T, A: Augment<T>, C> Drop for RbTree<T, A, C> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+// 区间的起点不能大于终点，与标准库的行为保持一致
+fn check_range<Q, G>(start: Bound<&Q>, end: Bound<&Q>, cmp: &G)
+where
+    Q: ?Sized,
+    G: Fn(&Q, &Q) -> Ordering,
+{
+    match (start, end) {
+        (Bound::Excluded(s), Bound::Excluded(e)) if cmp(s, e) == Ordering::Equal => {
+            panic!("range start and end are equal and excluded")
+        }
+        (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e))
+            if cmp(s, e) == Ordering::Greater =>
+        {
+            panic!("range start is greater than range end")
+        }
+        _ => {}
+    }
+}
+
+// 值是否在区间起点之后
+fn after_start<Q, G>(value: &Q, start: Bound<&Q>, cmp: &G) -> bool
+where
+    Q: ?Sized,
+    G: Fn(&Q, &Q) -> Ordering,
+{
+    match start {
+        Bound::Included(s) => cmp(value, s) != Ordering::Less,
+        Bound::Excluded(s) => cmp(value, s) == Ordering::Greater,
+        Bound::Unbounded => true,
+    }
+}
+
+// 值是否在区间终点之前
+fn before_end<Q, G>(value: &Q, end: Bound<&Q>, cmp: &G) -> bool
+where
+    Q: ?Sized,
+    G: Fn(&Q, &Q) -> Ordering,
+{
+    match end {
+        Bound::Included(e) => cmp(value, e) != Ordering::Greater,
+        Bound::Excluded(e) => cmp(value, e) == Ordering::Less,
+        Bound::Unbounded => true,
+    }
+}
+
+impl<T: Display + Default + Clone + Display, A: Augment<T>, C> Display for RbTree<T, A, C> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let root = self.root;
 
         let mut matrix = vec![vec![]];
-        fn fmt_node<T: Ord + Display + Clone>(
-            node: Option<NonNull<RbNode<T>>>,
+        fn fmt_node<T: Display + Clone, A: Augment<T>>(
+            node: Option<NonNull<RbNode<T, A>>>,
             depth: usize,
             matrix: &mut Vec<Vec<String>>,
         ) {
@@ -933,19 +1548,18 @@ impl<T: Ord + Display + Default + Clone + Display> Display for RbTree<T> {
                 fmt_node(unsafe { (*ptr).right }, depth + 1, matrix);
             } else {
                 matrix[depth].push(String::from("."));
-                return;
             }
         }
         fmt_node(root, 0, &mut matrix);
 
-        write!(f, "Size: {}\n", self.size)?;
+        writeln!(f, "Size: {}", self.size)?;
         let space = matrix.iter().map(|level| level.len()).max().unwrap_or(16) * 4;
-        for (i, level) in matrix.iter().enumerate() {
+        for level in matrix.iter() {
             write!(f, "{}", " ".repeat(space - level.len() * 2))?;
             for node in level {
                 write!(f, "{:3} ", node)?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
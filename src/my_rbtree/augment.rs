@@ -0,0 +1,197 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+};
+
+use super::{Comparator, RbNode, RbTree, after_start, before_end, check_range};
+
+/// 每个节点额外维护的子树摘要
+///
+/// 节点的摘要由左子树摘要、节点自身的值和右子树摘要合并得到，
+/// 旋转、插入和删除后会沿受影响的节点重新计算。
+/// combine需要满足结合律（例如计数、求和、最大值），`fold_range`才能用子树摘要拼出区间的结果
+pub trait Augment<T> {
+    type Summary;
+
+    fn combine(
+        left: Option<&Self::Summary>,
+        value: &T,
+        right: Option<&Self::Summary>,
+    ) -> Self::Summary;
+
+    /// 摘要中记录的子树元素个数，不记录时返回None
+    ///
+    /// `split_off`用它直接得到拆分后两边的长度，树的长度关系到内存安全，
+    /// 所以只有本模块中的`SubtreeSize`能提供：参数类型在外部无法命名，外部的实现不能覆盖这个方法
+    #[doc(hidden)]
+    fn subtree_len(_summary: &Self::Summary, _: Sealed) -> Option<usize> {
+        None
+    }
+}
+
+mod sealed {
+    pub struct Sealed;
+}
+
+pub(super) use sealed::Sealed;
+
+// 默认不维护任何摘要，零大小的摘要在更新时会被直接跳过
+impl<T> Augment<T> for () {
+    type Summary = ();
+
+    fn combine(_left: Option<&()>, _value: &T, _right: Option<&()>) {}
+}
+
+/// 维护子树大小，用于按排名查找
+pub struct SubtreeSize;
+
+impl<T> Augment<T> for SubtreeSize {
+    type Summary = usize;
+
+    fn combine(left: Option<&usize>, _value: &T, right: Option<&usize>) -> usize {
+        left.copied().unwrap_or(0) + 1 + right.copied().unwrap_or(0)
+    }
+
+    fn subtree_len(summary: &usize, _: Sealed) -> Option<usize> {
+        Some(*summary)
+    }
+}
+
+impl<T, A: Augment<T>, C> RbTree<T, A, C> {
+    /// 整棵树的摘要，空树返回None
+    pub fn summary(&self) -> Option<&A::Summary> {
+        self.root.map(|root| unsafe { &(*root.as_ptr()).summary })
+    }
+
+    /// 合并区间内所有元素的摘要，区间为空时返回None
+    ///
+    /// 只访问区间两端搜索路径上的O(log n)个节点，路径之间的整棵子树直接使用已有的摘要
+    pub fn fold_range<Q, R>(&self, range: R) -> Option<A::Summary>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        R: RangeBounds<Q>,
+        C: Comparator<Q>,
+    {
+        let cmp = |a: &Q, b: &Q| self.cmp.compare(a, b);
+        let (start, end) = (range.start_bound(), range.end_bound());
+        check_range(start, end, &cmp);
+
+        // 找到第一个落在区间内的节点，两端的搜索路径从这里分开
+        let mut current = self.root;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            let value = unsafe { (*node_ptr).value.borrow() };
+            if !after_start(value, start, &cmp) {
+                current = unsafe { (*node_ptr).right };
+            } else if !before_end(value, end, &cmp) {
+                current = unsafe { (*node_ptr).left };
+            } else {
+                let left = Self::fold_after(unsafe { (*node_ptr).left }, start, &cmp);
+                let right = Self::fold_before(unsafe { (*node_ptr).right }, end, &cmp);
+                return Some(A::combine(
+                    left.as_ref(),
+                    unsafe { &(*node_ptr).value },
+                    right.as_ref(),
+                ));
+            }
+        }
+        None
+    }
+
+    // 子树中所有在起点之后的元素的摘要
+    fn fold_after<Q, G>(
+        node: Option<NonNull<RbNode<T, A>>>,
+        start: Bound<&Q>,
+        cmp: &G,
+    ) -> Option<A::Summary>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        G: Fn(&Q, &Q) -> Ordering,
+    {
+        let node_ptr = node?.as_ptr();
+        unsafe {
+            if after_start((*node_ptr).value.borrow(), start, cmp) {
+                // 右子树整体都在起点之后
+                let left = Self::fold_after((*node_ptr).left, start, cmp);
+                let right = (*node_ptr).right.map(|r| &(*r.as_ptr()).summary);
+                Some(A::combine(left.as_ref(), &(*node_ptr).value, right))
+            } else {
+                Self::fold_after((*node_ptr).right, start, cmp)
+            }
+        }
+    }
+
+    // 子树中所有在终点之前的元素的摘要
+    fn fold_before<Q, G>(
+        node: Option<NonNull<RbNode<T, A>>>,
+        end: Bound<&Q>,
+        cmp: &G,
+    ) -> Option<A::Summary>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        G: Fn(&Q, &Q) -> Ordering,
+    {
+        let node_ptr = node?.as_ptr();
+        unsafe {
+            if before_end((*node_ptr).value.borrow(), end, cmp) {
+                // 左子树整体都在终点之前
+                let left = (*node_ptr).left.map(|l| &(*l.as_ptr()).summary);
+                let right = Self::fold_before((*node_ptr).right, end, cmp);
+                Some(A::combine(left, &(*node_ptr).value, right.as_ref()))
+            } else {
+                Self::fold_before((*node_ptr).left, end, cmp)
+            }
+        }
+    }
+}
+
+impl<T, C> RbTree<T, SubtreeSize, C> {
+    fn subtree_size(node: Option<NonNull<RbNode<T, SubtreeSize>>>) -> usize {
+        node.map_or(0, |n| unsafe { (*n.as_ptr()).summary })
+    }
+
+    /// 第k小的元素（从0开始计数）
+    pub fn select(&self, k: usize) -> Option<&T> {
+        let mut k = k;
+        let mut current = self.root;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            let left_size = Self::subtree_size(unsafe { (*node_ptr).left });
+            if k < left_size {
+                current = unsafe { (*node_ptr).left };
+            } else if k == left_size {
+                return Some(unsafe { &(*node_ptr).value });
+            } else {
+                k -= left_size + 1;
+                current = unsafe { (*node_ptr).right };
+            }
+        }
+        None
+    }
+
+    /// 严格小于key的元素个数
+    pub fn rank<Q>(&self, key: &Q) -> usize
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        let mut rank = 0;
+        let mut current = self.root;
+        while let Some(node) = current {
+            let node_ptr = node.as_ptr();
+            if self.cmp.compare(key, unsafe { (*node_ptr).value.borrow() }) != Ordering::Greater {
+                current = unsafe { (*node_ptr).left };
+            } else {
+                rank += Self::subtree_size(unsafe { (*node_ptr).left }) + 1;
+                current = unsafe { (*node_ptr).right };
+            }
+        }
+        rank
+    }
+}
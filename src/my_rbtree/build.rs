@@ -0,0 +1,114 @@
+use std::{cmp::Ordering, ptr::NonNull};
+
+use super::{Augment, Comparator, RbColor, RbNode, RbTree};
+
+impl<T, A: Augment<T>, C: Comparator<T> + Default> RbTree<T, A, C> {
+    /// 用已经排好序的元素构造完全平衡的树，O(n)，不需要逐个插入
+    ///
+    /// 相等的元素都会保留并按给出的顺序排列，与`insert_multi`相同。输入没有排好序时panic
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::default();
+        let values: Vec<T> = iter.into_iter().collect();
+        assert!(
+            values
+                .windows(2)
+                .all(|pair| tree.cmp.compare(&pair[0], &pair[1]) != Ordering::Greater),
+            "from_sorted_iter: input is not sorted"
+        );
+        let len = values.len();
+        tree.fill_sorted(values.into_iter(), len);
+        tree
+    }
+}
+
+// 任意顺序的输入先排序去重再构造，O(n log n)
+// 按集合的语义处理，相等的元素只保留最先出现的一个，与逐个insert的结果相同
+impl<T, A: Augment<T>, C: Comparator<T> + Default> FromIterator<T> for RbTree<T, A, C> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::default();
+        let mut values: Vec<T> = iter.into_iter().collect();
+        // 稳定排序，相等的元素保持原来的先后顺序
+        values.sort_by(|a, b| tree.cmp.compare(a, b));
+        values.dedup_by(|later, earlier| tree.cmp.compare(earlier, later) == Ordering::Equal);
+        let len = values.len();
+        tree.fill_sorted(values.into_iter(), len);
+        tree
+    }
+}
+
+impl<T, A: Augment<T>, C: Comparator<T> + Default, const N: usize> From<[T; N]>
+    for RbTree<T, A, C>
+{
+    fn from(values: [T; N]) -> Self {
+        Self::from_iter(values)
+    }
+}
+
+// 按集合的方式逐个插入，已有相等的元素时保留原来的
+impl<T, A: Augment<T>, C: Comparator<T>> Extend<T> for RbTree<T, A, C> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, A: Augment<T>, C: Comparator<T>> Extend<&'a T> for RbTree<T, A, C> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T, A: Augment<T>, C> RbTree<T, A, C> {
+    // 用len个已经有序的值构造完全平衡的树，替换当前的空树，O(n)
+    // 左右子树的大小最多相差1，所以除最底层外每一层都是满的。
+    // 最底层不满时把它染成红色，其余节点都是黑色，每条路径的黑高相同
+    pub(super) fn fill_sorted<I: Iterator<Item = T>>(&mut self, values: I, len: usize) {
+        debug_assert!(self.root.is_none());
+        let mut values = values;
+        let red_depth = match len.checked_ilog2() {
+            Some(depth) if !(len + 1).is_power_of_two() => Some(depth),
+            _ => None,
+        };
+        self.root = Self::build_balanced(&mut values, len, 0, red_depth);
+        self.size = len;
+        self.paranoid_check();
+    }
+
+    // 按中序依次从values中取值，构造len个节点的子树
+    // 递归深度是树高，只有O(log n)
+    fn build_balanced<I: Iterator<Item = T>>(
+        values: &mut I,
+        len: usize,
+        depth: u32,
+        red_depth: Option<u32>,
+    ) -> Option<NonNull<RbNode<T, A>>> {
+        if len == 0 {
+            return None;
+        }
+        let left_len = (len - 1) / 2;
+        let left = Self::build_balanced(values, left_len, depth + 1, red_depth);
+        let value = values
+            .next()
+            .expect("sorted input is shorter than its length");
+        let node = Box::into_raw(Box::new(RbNode::new(value)));
+        let node = unsafe { NonNull::new_unchecked(node) };
+        let right = Self::build_balanced(values, len - 1 - left_len, depth + 1, red_depth);
+
+        let node_ptr = node.as_ptr();
+        unsafe {
+            (*node_ptr).left = left;
+            (*node_ptr).right = right;
+            if red_depth == Some(depth) {
+                (*node_ptr).color = RbColor::Red;
+            } else {
+                (*node_ptr).color = RbColor::Black;
+            }
+            for child in [left, right].into_iter().flatten() {
+                (*child.as_ptr()).parent = Some(node);
+            }
+        }
+        Self::update_summary(node);
+        Some(node)
+    }
+}
@@ -0,0 +1,29 @@
+use std::cmp::Ordering;
+
+/// 决定树中元素顺序的比较器
+///
+/// 查找时传入的键类型Q可以与元素类型不同（见`Borrow`），
+/// 比较器需要能比较这种键
+pub trait Comparator<Q: ?Sized> {
+    fn compare(&self, a: &Q, b: &Q) -> Ordering;
+}
+
+/// 默认的比较器，直接使用`Ord`
+#[derive(Clone, Copy, Default)]
+pub struct NaturalOrder;
+
+impl<Q: Ord + ?Sized> Comparator<Q> for NaturalOrder {
+    fn compare(&self, a: &Q, b: &Q) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+// 闭包也可以直接作为比较器，例如`|a: &u64, b: &u64| b.cmp(a)`
+impl<Q: ?Sized, F> Comparator<Q> for F
+where
+    F: Fn(&Q, &Q) -> Ordering,
+{
+    fn compare(&self, a: &Q, b: &Q) -> Ordering {
+        self(a, b)
+    }
+}
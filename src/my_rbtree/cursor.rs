@@ -0,0 +1,255 @@
+use std::{borrow::Borrow, cmp::Ordering, error::Error, fmt, ptr::NonNull};
+
+use super::{Augment, Comparator, InsertPos, NaturalOrder, RbNode, RbTree};
+
+impl<T, A: Augment<T>, C> RbTree<T, A, C> {
+    /// 指向最小元素的游标，树为空时停在幽灵位置
+    pub fn cursor_front(&self) -> Cursor<'_, T, A, C> {
+        Cursor {
+            current: self.root.map(Self::find_left_node_right),
+            tree: self,
+        }
+    }
+
+    /// 指向最大元素的游标
+    pub fn cursor_back(&self) -> Cursor<'_, T, A, C> {
+        Cursor {
+            current: self.root.map(Self::find_right_node_left),
+            tree: self,
+        }
+    }
+
+    /// 指向大于等于key的最小元素的游标，没有这样的元素时停在幽灵位置
+    pub fn cursor_ceiling<Q>(&self, key: &Q) -> Cursor<'_, T, A, C>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        Cursor {
+            current: self.first_node_by(|v| self.cmp.compare(v.borrow(), key) != Ordering::Less),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, A, C> {
+        CursorMut {
+            current: self.root.map(Self::find_left_node_right),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, A, C> {
+        CursorMut {
+            current: self.root.map(Self::find_right_node_left),
+            tree: self,
+        }
+    }
+
+    pub fn cursor_ceiling_mut<Q>(&mut self, key: &Q) -> CursorMut<'_, T, A, C>
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q>,
+    {
+        CursorMut {
+            current: self.first_node_by(|v| self.cmp.compare(v.borrow(), key) != Ordering::Less),
+            tree: self,
+        }
+    }
+}
+
+/// 停在树中某个元素上的只读游标
+///
+/// 除了元素之外还有一个幽灵位置，它在最后一个元素之后、第一个元素之前，
+/// 从最后一个元素向后移动或从第一个元素向前移动都会到达这里，再移动一次回到另一端
+pub struct Cursor<'a, T, A: Augment<T> = (), C = NaturalOrder> {
+    current: Option<NonNull<RbNode<T, A>>>,
+    tree: &'a RbTree<T, A, C>,
+}
+
+impl<T, A: Augment<T>, C> Clone for Cursor<'_, T, A, C> {
+    fn clone(&self) -> Self {
+        Cursor {
+            current: self.current,
+            tree: self.tree,
+        }
+    }
+}
+
+impl<'a, T, A: Augment<T>, C> Cursor<'a, T, A, C> {
+    /// 当前元素，在幽灵位置时返回None
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = next_of(self.tree, self.current);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = prev_of(self.tree, self.current);
+    }
+
+    /// 下一个元素，不移动游标
+    pub fn peek_next(&self) -> Option<&'a T> {
+        next_of(self.tree, self.current).map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 上一个元素，不移动游标
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        prev_of(self.tree, self.current).map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+}
+
+/// 可以在当前位置插入和删除元素的游标，位置的规则与`Cursor`相同
+///
+/// 插入和删除直接在游标所在的节点上进行，不需要从根重新查找
+pub struct CursorMut<'a, T, A: Augment<T> = (), C = NaturalOrder> {
+    current: Option<NonNull<RbNode<T, A>>>,
+    tree: &'a mut RbTree<T, A, C>,
+}
+
+impl<'a, T, A: Augment<T>, C> CursorMut<'a, T, A, C> {
+    pub fn current(&self) -> Option<&T> {
+        self.current.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn move_next(&mut self) {
+        self.current = next_of(self.tree, self.current);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.current = prev_of(self.tree, self.current);
+    }
+
+    pub fn peek_next(&self) -> Option<&T> {
+        next_of(self.tree, self.current).map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn peek_prev(&self) -> Option<&T> {
+        prev_of(self.tree, self.current).map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    /// 在同一位置借出只读游标
+    pub fn as_cursor(&self) -> Cursor<'_, T, A, C> {
+        Cursor {
+            current: self.current,
+            tree: self.tree,
+        }
+    }
+
+    /// 删除并取回当前元素，游标移到下一个元素，在幽灵位置时什么也不做
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // 删除时移动的是节点而不是值，下一个节点删除后仍然有效
+        self.current = RbTree::<T, A, C>::next_node(node);
+        Some(self.tree.remove_node(node))
+    }
+
+    /// 在当前元素之前插入value，在幽灵位置时插入到最后，游标不移动
+    ///
+    /// value必须不小于上一个元素、不大于当前元素，否则不插入并把value放在错误中返回。
+    /// 与相邻元素相等时也可以插入，和`insert_multi`一样不检查重复
+    pub fn insert_before(&mut self, value: T) -> Result<(), UnorderedKeyError<T>>
+    where
+        C: Comparator<T>,
+    {
+        if !self.fits_between(self.peek_prev(), &value, self.current()) {
+            return Err(UnorderedKeyError(value));
+        }
+        // 有左子树时挂在上一个节点的右侧，否则直接挂在当前节点的左侧
+        let pos = match self.current {
+            Some(node) => match unsafe { (*node.as_ptr()).left } {
+                Some(left) => InsertPos {
+                    parent: Some(RbTree::<T, A, C>::find_right_node_left(left)),
+                    left: false,
+                },
+                None => InsertPos {
+                    parent: Some(node),
+                    left: true,
+                },
+            },
+            None => InsertPos {
+                parent: self.tree.root.map(RbTree::<T, A, C>::find_right_node_left),
+                left: false,
+            },
+        };
+        self.tree.insert_at(value, pos);
+        Ok(())
+    }
+
+    /// 在当前元素之后插入value，在幽灵位置时插入到最前，游标不移动
+    ///
+    /// value必须不小于当前元素、不大于下一个元素，规则与`insert_before`对称
+    pub fn insert_after(&mut self, value: T) -> Result<(), UnorderedKeyError<T>>
+    where
+        C: Comparator<T>,
+    {
+        if !self.fits_between(self.current(), &value, self.peek_next()) {
+            return Err(UnorderedKeyError(value));
+        }
+        let pos = match self.current {
+            Some(node) => match unsafe { (*node.as_ptr()).right } {
+                Some(right) => InsertPos {
+                    parent: Some(RbTree::<T, A, C>::find_left_node_right(right)),
+                    left: true,
+                },
+                None => InsertPos {
+                    parent: Some(node),
+                    left: false,
+                },
+            },
+            None => InsertPos {
+                parent: self.tree.root.map(RbTree::<T, A, C>::find_left_node_right),
+                left: true,
+            },
+        };
+        self.tree.insert_at(value, pos);
+        Ok(())
+    }
+
+    // value能否放在prev和next之间，为None的一侧不限制
+    fn fits_between(&self, prev: Option<&T>, value: &T, next: Option<&T>) -> bool
+    where
+        C: Comparator<T>,
+    {
+        let cmp = &self.tree.cmp;
+        prev.is_none_or(|p| cmp.compare(p, value) != Ordering::Greater)
+            && next.is_none_or(|n| cmp.compare(value, n) != Ordering::Greater)
+    }
+}
+
+/// `CursorMut`插入的元素与相邻元素的顺序不符，带回没有插入的元素
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnorderedKeyError<T>(pub T);
+
+impl<T> fmt::Display for UnorderedKeyError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "value is out of order with the cursor's neighbours")
+    }
+}
+
+impl<T: fmt::Debug> Error for UnorderedKeyError<T> {}
+
+// 游标向后移动一步，幽灵位置的下一个是第一个元素
+fn next_of<T, A: Augment<T>, C>(
+    tree: &RbTree<T, A, C>,
+    current: Option<NonNull<RbNode<T, A>>>,
+) -> Option<NonNull<RbNode<T, A>>> {
+    match current {
+        Some(node) => RbTree::<T, A, C>::next_node(node),
+        None => tree.root.map(RbTree::<T, A, C>::find_left_node_right),
+    }
+}
+
+// 游标向前移动一步，幽灵位置的上一个是最后一个元素
+fn prev_of<T, A: Augment<T>, C>(
+    tree: &RbTree<T, A, C>,
+    current: Option<NonNull<RbNode<T, A>>>,
+) -> Option<NonNull<RbNode<T, A>>> {
+    match current {
+        Some(node) => RbTree::<T, A, C>::prev_node(node),
+        None => tree.root.map(RbTree::<T, A, C>::find_right_node_left),
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    cmp::Ordering, iter::FusedIterator, marker::PhantomData, ops::RangeInclusive, ptr::NonNull,
+};
+
+use super::{Augment, RbNode, RbTree};
+
+// 树中实际存放的区间，先按起点再按终点排序，起点相同的区间也能区分开
+struct IntervalEntry<K, V> {
+    range: RangeInclusive<K>,
+    value: V,
+}
+
+impl<K: Ord, V> IntervalEntry<K, V> {
+    fn cmp_range(&self, range: &RangeInclusive<K>) -> Ordering {
+        self.range
+            .start()
+            .cmp(range.start())
+            .then_with(|| self.range.end().cmp(range.end()))
+    }
+
+    fn overlaps(&self, range: &RangeInclusive<K>) -> bool {
+        self.range.start() <= range.end() && self.range.end() >= range.start()
+    }
+}
+
+impl<K: Ord, V> PartialEq for IntervalEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, V> Eq for IntervalEntry<K, V> {}
+
+impl<K: Ord, V> PartialOrd for IntervalEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for IntervalEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_range(&other.range)
+    }
+}
+
+// 子树中所有区间终点的最大值
+struct MaxEnd;
+
+impl<K: Ord + Clone, V> Augment<IntervalEntry<K, V>> for MaxEnd {
+    type Summary = K;
+
+    fn combine(left: Option<&K>, value: &IntervalEntry<K, V>, right: Option<&K>) -> K {
+        let mut max = value.range.end();
+        for end in left.into_iter().chain(right) {
+            if end > max {
+                max = end;
+            }
+        }
+        max.clone()
+    }
+}
+
+type Node<K, V> = RbNode<IntervalEntry<K, V>, MaxEnd>;
+
+/// 基于红黑树的区间树，保存闭区间及其对应的值
+///
+/// 每个节点记录子树中区间终点的最大值，查询时可以跳过不可能相交的子树
+pub struct IntervalTree<K: Ord + Clone, V> {
+    tree: RbTree<IntervalEntry<K, V>, MaxEnd>,
+}
+
+impl<K: Ord + Clone, V> Default for IntervalTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V> IntervalTree<K, V> {
+    pub fn new() -> Self {
+        IntervalTree {
+            tree: RbTree::with_augment(MaxEnd),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 插入区间，允许重复和相互重叠的区间
+    ///
+    /// 起点与终点都相同的区间按插入的先后排列
+    ///
+    /// # Panics
+    ///
+    /// range的起点大于终点时panic
+    pub fn insert(&mut self, range: RangeInclusive<K>, value: V) {
+        assert!(
+            range.start() <= range.end(),
+            "interval start is greater than interval end"
+        );
+        self.tree.insert_multi(IntervalEntry { range, value });
+    }
+
+    /// 删除与range起点终点都相同的区间中最早插入的一个，返回它的值
+    pub fn remove(&mut self, range: &RangeInclusive<K>) -> Option<V> {
+        self.remove_where(range, |_| true)
+    }
+
+    /// 在与range起点终点都相同的区间中，按插入的先后删除第一个值满足pred的，返回它的值
+    pub fn remove_where<F>(&mut self, range: &RangeInclusive<K>, mut pred: F) -> Option<V>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        // 相同的区间在中序上相邻，从第一个开始向后找
+        let mut current = self
+            .tree
+            .first_node_by(|entry| entry.cmp_range(range) != Ordering::Less);
+        while let Some(node) = current {
+            let entry = unsafe { &(*node.as_ptr()).value };
+            if entry.cmp_range(range) != Ordering::Equal {
+                return None;
+            }
+            if pred(&entry.value) {
+                return Some(self.tree.remove_node(node).value);
+            }
+            current = RbTree::<IntervalEntry<K, V>, MaxEnd>::next_node(node);
+        }
+        None
+    }
+
+    /// 与range相交的所有区间，按起点从小到大排列，range的起点大于终点时为空
+    pub fn overlapping(&self, range: &RangeInclusive<K>) -> Overlapping<'_, K, V> {
+        let query = range.clone();
+        // 空区间与任何区间都不相交，first_overlap和next_overlap都假定query非空
+        let next = if query.start() <= query.end() {
+            first_overlap(self.tree.root, &query)
+        } else {
+            None
+        };
+        Overlapping {
+            query,
+            next,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 包含point的所有区间
+    pub fn containing(&self, point: &K) -> Overlapping<'_, K, V> {
+        self.overlapping(&(point.clone()..=point.clone()))
+    }
+
+    /// 任意一个包含point的区间，只需要一次从根到叶的查找
+    pub fn any_containing(&self, point: &K) -> Option<(&RangeInclusive<K>, &V)> {
+        self.containing(point).next()
+    }
+}
+
+// 子树中按起点排序的第一个与query相交的区间
+// 左子树中有终点不小于query起点的区间时，答案要么在左子树中，要么根本不存在，
+// 所以每一层只需要向一个方向走
+fn first_overlap<K: Ord + Clone, V>(
+    node: Option<NonNull<Node<K, V>>>,
+    query: &RangeInclusive<K>,
+) -> Option<NonNull<Node<K, V>>> {
+    let mut current = node;
+    while let Some(n) = current {
+        let node_ptr = n.as_ptr();
+        unsafe {
+            if (*node_ptr).summary < *query.start() {
+                return None;
+            }
+            if let Some(left) = (*node_ptr).left
+                && (*left.as_ptr()).summary >= *query.start()
+            {
+                current = Some(left);
+                continue;
+            }
+            let entry = &(*node_ptr).value;
+            if entry.range.start() > query.end() {
+                return None;
+            }
+            if entry.overlaps(query) {
+                return Some(n);
+            }
+            current = (*node_ptr).right;
+        }
+    }
+    None
+}
+
+// 中序上位于node之后的第一个相交区间，沿父指针向上，不需要额外的栈
+fn next_overlap<K: Ord + Clone, V>(
+    node: NonNull<Node<K, V>>,
+    query: &RangeInclusive<K>,
+) -> Option<NonNull<Node<K, V>>> {
+    unsafe {
+        if let Some(found) = first_overlap((*node.as_ptr()).right, query) {
+            return Some(found);
+        }
+        let mut child = node;
+        let mut parent = (*node.as_ptr()).parent;
+        while let Some(p) = parent {
+            if (*p.as_ptr()).left == Some(child) {
+                // 从左侧回到祖先，接下来依次检查祖先和它的右子树
+                let entry = &(*p.as_ptr()).value;
+                if entry.range.start() > query.end() {
+                    return None;
+                }
+                if entry.overlaps(query) {
+                    return Some(p);
+                }
+                if let Some(found) = first_overlap((*p.as_ptr()).right, query) {
+                    return Some(found);
+                }
+            }
+            child = p;
+            parent = (*p.as_ptr()).parent;
+        }
+    }
+    None
+}
+
+/// 与查询区间相交的区间迭代器，由`IntervalTree::overlapping`和`IntervalTree::containing`创建
+pub struct Overlapping<'a, K: Ord + Clone, V> {
+    query: RangeInclusive<K>,
+    next: Option<NonNull<Node<K, V>>>,
+    _marker: PhantomData<&'a IntervalEntry<K, V>>,
+}
+
+impl<'a, K: Ord + Clone, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (&'a RangeInclusive<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = next_overlap(node, &self.query);
+        let entry = unsafe { &(*node.as_ptr()).value };
+        Some((&entry.range, &entry.value))
+    }
+}
+
+impl<K: Ord + Clone, V> FusedIterator for Overlapping<'_, K, V> {}
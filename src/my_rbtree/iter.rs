@@ -0,0 +1,322 @@
+use std::{iter::FusedIterator, marker::PhantomData, ptr::NonNull};
+
+use super::{Augment, RbNode, RbTree};
+
+/// 按从小到大的顺序借用树中元素的迭代器
+///
+/// 沿父指针在节点间移动，不需要额外的栈空间
+pub struct Iter<'a, T, A: Augment<T> = ()> {
+    front: Option<NonNull<RbNode<T, A>>>,
+    back: Option<NonNull<RbNode<T, A>>>,
+    // 剩余元素个数，front和back相遇后归零，避免两端交叉
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T, A: Augment<T>> Iter<'a, T, A> {
+    pub(super) fn new(
+        front: Option<NonNull<RbNode<T, A>>>,
+        back: Option<NonNull<RbNode<T, A>>>,
+        len: usize,
+    ) -> Self {
+        Iter {
+            front,
+            back,
+            len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, A: Augment<T>> Clone for Iter<'_, T, A> {
+    fn clone(&self) -> Self {
+        Iter::new(self.front, self.back, self.len)
+    }
+}
+
+impl<'a, T, A: Augment<T>> Iterator for Iter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.front?;
+        self.len -= 1;
+        self.front = RbTree::<T, A>::next_node(node);
+        Some(unsafe { &(*node.as_ptr()).value })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, A: Augment<T>> DoubleEndedIterator for Iter<'_, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.back?;
+        self.len -= 1;
+        self.back = RbTree::<T, A>::prev_node(node);
+        Some(unsafe { &(*node.as_ptr()).value })
+    }
+}
+
+impl<T, A: Augment<T>> ExactSizeIterator for Iter<'_, T, A> {}
+
+impl<T, A: Augment<T>> FusedIterator for Iter<'_, T, A> {}
+
+impl<'a, T, A: Augment<T>, C> IntoIterator for &'a RbTree<T, A, C> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// 按从小到大的顺序取出树中元素的迭代器
+///
+/// 每次从剩余部分的两端摘下一个节点并释放，不做平衡修复
+pub struct IntoIter<T, A: Augment<T> = ()> {
+    root: Option<NonNull<RbNode<T, A>>>,
+    front: Option<NonNull<RbNode<T, A>>>,
+    back: Option<NonNull<RbNode<T, A>>>,
+    len: usize,
+}
+
+impl<T, A: Augment<T>> IntoIter<T, A> {
+    pub(super) fn new(root: Option<NonNull<RbNode<T, A>>>, len: usize) -> Self {
+        IntoIter {
+            root,
+            front: root.map(RbTree::<T, A>::find_left_node_right),
+            back: root.map(RbTree::<T, A>::find_right_node_left),
+            len,
+        }
+    }
+
+    // 两端指向同一个节点时它是剩下的唯一一个节点，取走后两端都结束
+    // 是否结束只看指针，len只用于size_hint，即使len与节点数不一致也不会重复释放
+    fn take_last(&mut self) {
+        self.root = None;
+        self.front = None;
+        self.back = None;
+        self.len = 0;
+    }
+
+    // 用子节点顶替被摘下的节点，剩余部分仍是一棵二叉搜索树
+    fn splice(
+        &mut self,
+        node: NonNull<RbNode<T, A>>,
+        child: Option<NonNull<RbNode<T, A>>>,
+    ) -> Option<NonNull<RbNode<T, A>>> {
+        let parent = unsafe { (*node.as_ptr()).parent };
+        unsafe {
+            match parent {
+                Some(p) if (*p.as_ptr()).left == Some(node) => (*p.as_ptr()).left = child,
+                Some(p) => (*p.as_ptr()).right = child,
+                None => self.root = child,
+            }
+            if let Some(c) = child {
+                (*c.as_ptr()).parent = parent;
+            }
+        }
+        parent
+    }
+}
+
+impl<T, A: Augment<T>> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.front?;
+        if self.back == Some(node) {
+            self.take_last();
+        } else {
+            // 最小节点没有左子树，用右子树顶替即可
+            let right = unsafe { (*node.as_ptr()).right };
+            let parent = self.splice(node, right);
+            self.front = match right {
+                Some(r) => Some(RbTree::<T, A>::find_left_node_right(r)),
+                None => parent,
+            };
+        }
+        self.len = self.len.saturating_sub(1);
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        Some(node.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T, A: Augment<T>> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.back?;
+        if self.front == Some(node) {
+            self.take_last();
+        } else {
+            // 最大节点没有右子树，用左子树顶替即可
+            let left = unsafe { (*node.as_ptr()).left };
+            let parent = self.splice(node, left);
+            self.back = match left {
+                Some(l) => Some(RbTree::<T, A>::find_right_node_left(l)),
+                None => parent,
+            };
+        }
+        self.len = self.len.saturating_sub(1);
+        let node = unsafe { Box::from_raw(node.as_ptr()) };
+        Some(node.value)
+    }
+}
+
+impl<T, A: Augment<T>> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Augment<T>> FusedIterator for IntoIter<T, A> {}
+
+impl<T, A: Augment<T>> Drop for IntoIter<T, A> {
+    fn drop(&mut self) {
+        // 提前丢弃时释放剩余的节点
+        for _ in self.by_ref() {}
+    }
+}
+
+impl<T, A: Augment<T>, C> IntoIterator for RbTree<T, A, C> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter::new(self.root.take(), self.size)
+    }
+}
+
+// 区间两端的节点（都包含在内），沿父指针向中间移动，相遇后结束
+pub(super) struct RawRange<T, A: Augment<T> = ()> {
+    front: Option<NonNull<RbNode<T, A>>>,
+    back: Option<NonNull<RbNode<T, A>>>,
+}
+
+impl<T, A: Augment<T>> RawRange<T, A> {
+    pub(super) fn new(
+        front: Option<NonNull<RbNode<T, A>>>,
+        back: Option<NonNull<RbNode<T, A>>>,
+    ) -> Self {
+        RawRange { front, back }
+    }
+
+    pub(super) fn next_node(&mut self) -> Option<NonNull<RbNode<T, A>>> {
+        let node = self.front?;
+        if self.back == Some(node) {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.front = RbTree::<T, A>::next_node(node);
+        }
+        Some(node)
+    }
+
+    pub(super) fn next_back_node(&mut self) -> Option<NonNull<RbNode<T, A>>> {
+        let node = self.back?;
+        if self.front == Some(node) {
+            self.front = None;
+            self.back = None;
+        } else {
+            self.back = RbTree::<T, A>::prev_node(node);
+        }
+        Some(node)
+    }
+}
+
+impl<T, A: Augment<T>> Clone for RawRange<T, A> {
+    fn clone(&self) -> Self {
+        RawRange::new(self.front, self.back)
+    }
+}
+
+/// 按从小到大的顺序借用区间内元素的迭代器，由`RbTree::range`创建
+pub struct Range<'a, T, A: Augment<T> = ()> {
+    raw: RawRange<T, A>,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T, A: Augment<T>> Range<'_, T, A> {
+    pub(super) fn new(raw: RawRange<T, A>) -> Self {
+        Range {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, A: Augment<T>> Clone for Range<'_, T, A> {
+    fn clone(&self) -> Self {
+        Range::new(self.raw.clone())
+    }
+}
+
+impl<'a, T, A: Augment<T>> Iterator for Range<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw
+            .next_node()
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+}
+
+impl<T, A: Augment<T>> DoubleEndedIterator for Range<'_, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.raw
+            .next_back_node()
+            .map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+}
+
+impl<T, A: Augment<T>> FusedIterator for Range<'_, T, A> {}
+
+/// 按从小到大的顺序删除并取出满足条件的元素，由`RbTree::extract_if`创建
+///
+/// 只在调用next时向前走，提前丢弃时剩下的元素都留在树中
+pub struct ExtractIf<'a, T, A: Augment<T>, C, F> {
+    tree: &'a mut RbTree<T, A, C>,
+    // 下一个要检查的节点
+    next: Option<NonNull<RbNode<T, A>>>,
+    pred: F,
+}
+
+impl<'a, T, A: Augment<T>, C, F> ExtractIf<'a, T, A, C, F> {
+    pub(super) fn new(tree: &'a mut RbTree<T, A, C>, pred: F) -> Self {
+        ExtractIf {
+            next: tree.root.map(RbTree::<T, A, C>::find_left_node_right),
+            tree,
+            pred,
+        }
+    }
+}
+
+impl<T, A: Augment<T>, C, F> Iterator for ExtractIf<'_, T, A, C, F>
+where
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.next {
+            // 删除时移动的是节点而不是值，先记下的后继节点在删除后仍然有效
+            self.next = RbTree::<T, A, C>::next_node(node);
+            if (self.pred)(unsafe { &(*node.as_ptr()).value }) {
+                return Some(self.tree.remove_node(node));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.tree.len()))
+    }
+}
+
+impl<T, A: Augment<T>, C, F> FusedIterator for ExtractIf<'_, T, A, C, F> where F: FnMut(&T) -> bool {}
@@ -0,0 +1,287 @@
+use std::{borrow::Borrow, cmp::Ordering, ptr::NonNull};
+
+use super::{Augment, Comparator, RbColor, RbNode, RbTree, augment::Sealed, validate::black_count};
+
+// 一棵独立的子树：根节点和它的黑高
+// 黑高是从根到空叶子的路径上黑节点的个数，包含根本身，空树的黑高为0
+type Part<T, A> = (Option<NonNull<RbNode<T, A>>>, usize);
+
+impl<T, A: Augment<T>, C> RbTree<T, A, C> {
+    /// 把other的所有元素移到self中，other变为空树
+    ///
+    /// 一棵树的元素都小于另一棵时，取较大一棵树的最小元素作为中间节点把两棵树连起来，O(log n)。
+    /// 范围有重叠时退化为O(n + m)的归并，按集合的语义处理，两边都有的元素保留other中的
+    pub fn append(&mut self, other: &mut Self)
+    where
+        C: Comparator<T>,
+    {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            std::mem::swap(&mut self.root, &mut other.root);
+            std::mem::swap(&mut self.size, &mut other.size);
+            return;
+        }
+        let total = self.size + other.size;
+        let (self_first, self_last) = (self.first().unwrap(), self.last().unwrap());
+        let (other_first, other_last) = (other.first().unwrap(), other.last().unwrap());
+        let root = if self.cmp.compare(self_last, other_first) == Ordering::Less {
+            let pivot = other.detach_first();
+            Self::join(self.take_part(), pivot, other.take_part()).0
+        } else if self.cmp.compare(other_last, self_first) == Ordering::Less {
+            let pivot = self.detach_first();
+            Self::join(other.take_part(), pivot, self.take_part()).0
+        } else {
+            self.append_overlapping(other);
+            return;
+        };
+        self.root = Some(root);
+        self.size = total;
+        self.paranoid_check();
+    }
+
+    /// 把不小于key的元素分出来作为新树返回，self中留下小于key的元素
+    ///
+    /// 拆分本身是O(log n)。A为`SubtreeSize`时两边的长度直接从根的摘要读出，
+    /// 总共O(log n)；否则要从两棵树的开头同时数，较小的一边数完即可，
+    /// 额外需要O(min(k, n - k))，k为留在self中的元素个数
+    pub fn split_off<Q>(&mut self, key: &Q) -> Self
+    where
+        Q: ?Sized,
+        T: Borrow<Q>,
+        C: Comparator<Q> + Clone,
+    {
+        let total = self.size;
+        let whole = self.take_part();
+        let cmp = &self.cmp;
+        let (left, right) = Self::split(whole, &mut |v: &T| {
+            cmp.compare(v.borrow(), key) != Ordering::Less
+        });
+        let (left_len, right_len) = match Self::part_len(right.0) {
+            Some(right_len) if right_len <= total => (total - right_len, right_len),
+            _ => Self::count_parts(left.0, right.0, total),
+        };
+
+        let mut other = Self::with_augment_and_comparator_inner(self.cmp.clone());
+        self.root = left.0;
+        self.size = left_len;
+        other.root = right.0;
+        other.size = right_len;
+        self.paranoid_check();
+        other.paranoid_check();
+        other
+    }
+
+    // 取走整棵树，树变为空树
+    fn take_part(&mut self) -> Part<T, A> {
+        self.size = 0;
+        let root = self.root.take();
+        (root, black_height(root))
+    }
+
+    // 从树中摘下最小的节点但不释放，留给join作为中间节点
+    fn detach_first(&mut self) -> NonNull<RbNode<T, A>> {
+        let node = Self::find_left_node_right(self.root.unwrap());
+        self.delete_node(node.as_ptr());
+        self.size -= 1;
+        node
+    }
+
+    // 用pivot把left和right连接成一棵树，返回新的根节点和黑高
+    // left中的元素都在pivot之前，right中的都在pivot之后，两边的根可以是红色
+    //
+    // 黑高相同时pivot直接作为根。否则沿较高一棵树靠近较矮一侧的边界向下，
+    // 找到与较矮的树黑高相同的黑节点，用红色的pivot把它和较矮的树连起来挂在原来的位置，
+    // 经过pivot的路径黑高不变，剩下的连续红节点和插入时一样向上修复。
+    // 花费的时间与两边黑高的差成正比
+    fn join(
+        left: Part<T, A>,
+        pivot: NonNull<RbNode<T, A>>,
+        right: Part<T, A>,
+    ) -> (NonNull<RbNode<T, A>>, usize) {
+        let (left, left_height) = Self::blacken_root(left);
+        let (right, right_height) = Self::blacken_root(right);
+        let pivot_ptr = pivot.as_ptr();
+        unsafe {
+            (*pivot_ptr).parent = None;
+        }
+
+        let (mut root, height) = match left_height.cmp(&right_height) {
+            Ordering::Equal => {
+                unsafe {
+                    (*pivot_ptr).color = RbColor::Black;
+                }
+                Self::link(pivot, left, right);
+                return (pivot, left_height + 1);
+            }
+            Ordering::Greater => {
+                let parent = Self::find_join_point(left.unwrap(), left_height, right_height, false);
+                Self::link(pivot, unsafe { (*parent.as_ptr()).right }, right);
+                unsafe {
+                    (*parent.as_ptr()).right = Some(pivot);
+                    (*pivot_ptr).parent = Some(parent);
+                }
+                (left, left_height)
+            }
+            Ordering::Less => {
+                let parent = Self::find_join_point(right.unwrap(), right_height, left_height, true);
+                Self::link(pivot, left, unsafe { (*parent.as_ptr()).left });
+                unsafe {
+                    (*parent.as_ptr()).left = Some(pivot);
+                    (*pivot_ptr).parent = Some(parent);
+                }
+                (right, right_height)
+            }
+        };
+        unsafe {
+            (*pivot_ptr).color = RbColor::Red;
+        }
+        Self::update_summary_to_root(unsafe { (*pivot_ptr).parent });
+        Self::insert_fixup_from(&raw mut root, pivot);
+        // 修复一直合并到根节点时根被染成了红色，染回黑色后黑高加一
+        let (root, height) = Self::blacken_root((root, height));
+        (root.unwrap(), height)
+    }
+
+    // 从root（黑色，黑高为height）沿左边界或右边界向下，
+    // 找到黑高为target的第一个黑节点（target为0时是空位置），返回它的父节点
+    fn find_join_point(
+        root: NonNull<RbNode<T, A>>,
+        height: usize,
+        target: usize,
+        left: bool,
+    ) -> NonNull<RbNode<T, A>> {
+        debug_assert!(height > target);
+        let child = |node: NonNull<RbNode<T, A>>| unsafe {
+            if left {
+                (*node.as_ptr()).left
+            } else {
+                (*node.as_ptr()).right
+            }
+        };
+        let mut parent = root;
+        // current子树的黑高
+        let mut current_height = height - black_count(root);
+        let mut current = child(root);
+        while let Some(node) = current {
+            if current_height == target && black_count(node) == 1 {
+                break;
+            }
+            parent = node;
+            current_height -= black_count(node);
+            current = child(node);
+        }
+        debug_assert_eq!(current_height, target);
+        parent
+    }
+
+    // 把root为根、黑高为height的子树分成两部分，goes_right为真的元素分到右边，
+    // goes_right在中序上须是先假后真
+    // 沿查找路径向下递归，回来时把路径两侧摘下的子树依次join起来，各次join的花费加起来是O(log n)。
+    // 递归深度是树高
+    fn split<F>(part: Part<T, A>, goes_right: &mut F) -> (Part<T, A>, Part<T, A>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let (Some(node), height) = part else {
+            return ((None, 0), (None, 0));
+        };
+        let node_ptr = node.as_ptr();
+        // 两个子节点的黑高相同，都是去掉node本身后的黑高
+        let child_height = height - black_count(node);
+        let (left, right) = unsafe { ((*node_ptr).left, (*node_ptr).right) };
+        unsafe {
+            (*node_ptr).left = None;
+            (*node_ptr).right = None;
+            for child in [left, right].into_iter().flatten() {
+                (*child.as_ptr()).parent = None;
+            }
+        }
+
+        if goes_right(unsafe { &(*node_ptr).value }) {
+            let (less, greater) = Self::split((left, child_height), goes_right);
+            let (root, height) = Self::join(greater, node, (right, child_height));
+            (less, (Some(root), height))
+        } else {
+            let (less, greater) = Self::split((right, child_height), goes_right);
+            let (root, height) = Self::join((left, child_height), node, less);
+            ((Some(root), height), greater)
+        }
+    }
+
+    // 作为独立的树：断开根的父指针，根是红色时染黑，黑高加一
+    fn blacken_root(part: Part<T, A>) -> Part<T, A> {
+        let (root, height) = part;
+        let Some(node) = root else {
+            return part;
+        };
+        let node_ptr = node.as_ptr();
+        unsafe {
+            (*node_ptr).parent = None;
+            if (*node_ptr).color == RbColor::Red {
+                (*node_ptr).color = RbColor::Black;
+                return (root, height + 1);
+            }
+        }
+        part
+    }
+
+    // 把left和right挂为node的左右子树，重新计算node的摘要
+    fn link(
+        node: NonNull<RbNode<T, A>>,
+        left: Option<NonNull<RbNode<T, A>>>,
+        right: Option<NonNull<RbNode<T, A>>>,
+    ) {
+        let node_ptr = node.as_ptr();
+        unsafe {
+            (*node_ptr).left = left;
+            (*node_ptr).right = right;
+            for child in [left, right].into_iter().flatten() {
+                (*child.as_ptr()).parent = Some(node);
+            }
+        }
+        Self::update_summary(node);
+    }
+
+    // A为SubtreeSize时直接从摘要读出子树的大小
+    fn part_len(root: Option<NonNull<RbNode<T, A>>>) -> Option<usize> {
+        match root {
+            None => Some(0),
+            Some(node) => A::subtree_len(unsafe { &(*node.as_ptr()).summary }, Sealed),
+        }
+    }
+
+    // 两棵树从最小的元素开始同时向后数，较小的一棵数完就能得到两边的元素个数
+    fn count_parts(
+        left: Option<NonNull<RbNode<T, A>>>,
+        right: Option<NonNull<RbNode<T, A>>>,
+        total: usize,
+    ) -> (usize, usize) {
+        let mut left = left.map(Self::find_left_node_right);
+        let mut right = right.map(Self::find_left_node_right);
+        let mut count = 0;
+        loop {
+            match (left, right) {
+                (Some(l), Some(r)) => {
+                    left = Self::next_node(l);
+                    right = Self::next_node(r);
+                    count += 1;
+                }
+                (None, _) => return (count, total - count),
+                (_, None) => return (total - count, count),
+            }
+        }
+    }
+}
+
+// 沿最左边的路径数出子树的黑高
+fn black_height<T, A: Augment<T>>(root: Option<NonNull<RbNode<T, A>>>) -> usize {
+    let mut height = 0;
+    let mut current = root;
+    while let Some(node) = current {
+        height += black_count(node);
+        current = unsafe { (*node.as_ptr()).left };
+    }
+    height
+}
@@ -0,0 +1,337 @@
+use std::{
+    borrow::Borrow, cmp::Ordering, iter::FusedIterator, marker::PhantomData, ops::RangeBounds,
+    ptr::NonNull,
+};
+
+use super::{InsertPos, RawRange, RbNode, RbTree};
+
+// 树中实际存放的键值对，只按键比较大小
+struct MapEntry<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K: Ord, V> PartialEq for MapEntry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: Ord, V> Eq for MapEntry<K, V> {}
+
+impl<K: Ord, V> PartialOrd for MapEntry<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for MapEntry<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// 基于红黑树的有序映射，插入删除的平衡修复与`RbTree`共用
+pub struct RbTreeMap<K: Ord, V> {
+    tree: RbTree<MapEntry<K, V>>,
+}
+
+impl<K: Ord, V> Default for RbTreeMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> RbTreeMap<K, V> {
+    pub fn new() -> Self {
+        RbTreeMap {
+            tree: RbTree::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// 插入键值对，键已存在时替换旧值并返回
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// 查找key对应的位置，之后的读取、修改或插入都不需要再次查找
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.tree.search_by(|entry| key.cmp(&entry.key)) {
+            Ok(node) => Entry::Occupied(OccupiedEntry { node, map: self }),
+            Err(pos) => Entry::Vacant(VacantEntry {
+                key,
+                pos,
+                map: self,
+            }),
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        self.tree
+            .find_by(|entry| key.cmp(entry.key.borrow()))
+            .map(|node| unsafe { &(*node.as_ptr()).value.value })
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        self.tree
+            .find_by(|entry| key.cmp(entry.key.borrow()))
+            .map(|node| unsafe { &mut (*node.as_ptr()).value.value })
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        self.tree
+            .find_by(|entry| key.cmp(entry.key.borrow()))
+            .is_some()
+    }
+
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        Range {
+            raw: self
+                .tree
+                .range_nodes_by(&range, |entry| entry.key.borrow(), Q::cmp),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn range_mut<Q, R>(&mut self, range: R) -> RangeMut<'_, K, V>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+        R: RangeBounds<Q>,
+    {
+        RangeMut {
+            raw: self
+                .tree
+                .range_nodes_by(&range, |entry| entry.key.borrow(), Q::cmp),
+            _marker: PhantomData,
+        }
+    }
+
+    /// 删除键并返回被删除的键值对
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        self.remove_entry(key)
+    }
+
+    /// 删除键并返回被删除的键值对，与`remove`相同，名字与`BTreeMap::remove_entry`一致
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        let node = self.tree.find_by(|entry| key.cmp(entry.key.borrow()))?;
+        let entry = self.tree.remove_node(node);
+        Some((entry.key, entry.value))
+    }
+}
+
+/// `RbTreeMap::entry`返回的位置，键可能已存在也可能不存在
+pub enum Entry<'a, K: Ord, V> {
+    Vacant(VacantEntry<'a, K, V>),
+    Occupied(OccupiedEntry<'a, K, V>),
+}
+
+/// 键不存在时的位置，记录了查找结束时的父节点
+pub struct VacantEntry<'a, K: Ord, V> {
+    key: K,
+    pos: InsertPos<MapEntry<K, V>, ()>,
+    map: &'a mut RbTreeMap<K, V>,
+}
+
+/// 键已存在时的位置，直接指向树中的节点
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    node: NonNull<RbNode<MapEntry<K, V>, ()>>,
+    map: &'a mut RbTreeMap<K, V>,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    /// 键不存在时插入default，返回值的可变引用
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// 键不存在时插入f的结果，f只在需要插入时调用
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(f()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// 键已存在时用f修改值
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// 在记录的位置挂入新节点并修复，返回值的可变引用
+    pub fn insert(self, value: V) -> &'a mut V {
+        let entry = MapEntry {
+            key: self.key,
+            value,
+        };
+        let node = self.map.tree.insert_at(entry, self.pos);
+        unsafe { &mut (*node.as_ptr()).value.value }
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        unsafe { &(*self.node.as_ptr()).value.key }
+    }
+
+    pub fn get(&self) -> &V {
+        unsafe { &(*self.node.as_ptr()).value.value }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        unsafe { &mut (*self.node.as_ptr()).value.value }
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        unsafe { &mut (*self.node.as_ptr()).value.value }
+    }
+
+    /// 替换值并返回旧值
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// 从树中删除这个节点，返回它的值
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        let entry = self.map.tree.remove_node(self.node);
+        (entry.key, entry.value)
+    }
+}
+
+/// 按键从小到大借用区间内键值对的迭代器，由`RbTreeMap::range`创建
+pub struct Range<'a, K: Ord, V> {
+    raw: RawRange<MapEntry<K, V>>,
+    _marker: PhantomData<&'a MapEntry<K, V>>,
+}
+
+impl<K: Ord, V> Clone for Range<'_, K, V> {
+    fn clone(&self) -> Self {
+        Range {
+            raw: self.raw.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next_node().map(|node| {
+            let entry = unsafe { &(*node.as_ptr()).value };
+            (&entry.key, &entry.value)
+        })
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for Range<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.raw.next_back_node().map(|node| {
+            let entry = unsafe { &(*node.as_ptr()).value };
+            (&entry.key, &entry.value)
+        })
+    }
+}
+
+impl<K: Ord, V> FusedIterator for Range<'_, K, V> {}
+
+/// 按键从小到大遍历区间，可修改值的迭代器，由`RbTreeMap::range_mut`创建
+pub struct RangeMut<'a, K: Ord, V> {
+    raw: RawRange<MapEntry<K, V>>,
+    _marker: PhantomData<&'a mut MapEntry<K, V>>,
+}
+
+impl<'a, K: Ord, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next_node().map(|node| {
+            let entry = unsafe { &mut (*node.as_ptr()).value };
+            (&entry.key, &mut entry.value)
+        })
+    }
+}
+
+impl<K: Ord, V> DoubleEndedIterator for RangeMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.raw.next_back_node().map(|node| {
+            let entry = unsafe { &mut (*node.as_ptr()).value };
+            (&entry.key, &mut entry.value)
+        })
+    }
+}
+
+impl<K: Ord, V> FusedIterator for RangeMut<'_, K, V> {}
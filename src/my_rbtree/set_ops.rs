@@ -0,0 +1,252 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    iter::{FusedIterator, Peekable},
+    marker::PhantomData,
+};
+
+use super::{Augment, Comparator, IntoIter, Iter, NaturalOrder, RbTree};
+
+// 归并时的一步：只在左边、只在右边或两边都有
+enum Merged<X> {
+    Left(X),
+    Right(X),
+    Both(X, X),
+}
+
+// 两个有序序列的归并，相等的元素两边一起取出
+struct MergeIter<'c, T, I: Iterator, C> {
+    left: Peekable<I>,
+    right: Peekable<I>,
+    cmp: &'c C,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<'c, T, I, C> MergeIter<'c, T, I, C>
+where
+    I: Iterator,
+    I::Item: Borrow<T>,
+    C: Comparator<T>,
+{
+    fn new(left: I, right: I, cmp: &'c C) -> Self {
+        MergeIter {
+            left: left.peekable(),
+            right: right.peekable(),
+            cmp,
+            _marker: PhantomData,
+        }
+    }
+
+    fn left_done(&mut self) -> bool {
+        self.left.peek().is_none()
+    }
+
+    fn right_done(&mut self) -> bool {
+        self.right.peek().is_none()
+    }
+}
+
+impl<T, I, C> Iterator for MergeIter<'_, T, I, C>
+where
+    I: Iterator,
+    I::Item: Borrow<T>,
+    C: Comparator<T>,
+{
+    type Item = Merged<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let order = match (self.left.peek(), self.right.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(l), Some(r)) => self.cmp.compare(l.borrow(), r.borrow()),
+        };
+        Some(match order {
+            Ordering::Less => Merged::Left(self.left.next()?),
+            Ordering::Greater => Merged::Right(self.right.next()?),
+            Ordering::Equal => Merged::Both(self.left.next()?, self.right.next()?),
+        })
+    }
+}
+
+impl<T, A: Augment<T>, C: Comparator<T>> RbTree<T, A, C> {
+    /// 并集，按从小到大的顺序惰性产生，两边都有的元素取self中的
+    ///
+    /// 集合运算都按集合的语义处理，两棵树应当都没有重复的元素
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T, A, C> {
+        Union {
+            merge: MergeIter::new(self.iter(), other.iter(), &self.cmp),
+        }
+    }
+
+    /// 交集，任意一边遍历完就结束
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, A, C> {
+        Intersection {
+            merge: MergeIter::new(self.iter(), other.iter(), &self.cmp),
+        }
+    }
+
+    /// 差集，在self中但不在other中的元素
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T, A, C> {
+        Difference {
+            merge: MergeIter::new(self.iter(), other.iter(), &self.cmp),
+        }
+    }
+
+    /// 对称差，只在其中一边出现的元素
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T, A, C> {
+        SymmetricDifference {
+            merge: MergeIter::new(self.iter(), other.iter(), &self.cmp),
+        }
+    }
+
+    /// self的元素是否都在other中，遇到第一个不在other中的元素就返回
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// 两边是否没有相同的元素，遇到第一个相同的元素就返回
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    /// 消耗两棵树，得到它们的并集，O(n + m)
+    pub fn into_union(self, other: Self) -> Self {
+        self.into_merged(other, |merged| match merged {
+            Merged::Left(x) | Merged::Right(x) | Merged::Both(x, _) => Some(x),
+        })
+    }
+
+    pub fn into_intersection(self, other: Self) -> Self {
+        self.into_merged(other, |merged| match merged {
+            Merged::Both(x, _) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn into_difference(self, other: Self) -> Self {
+        self.into_merged(other, |merged| match merged {
+            Merged::Left(x) => Some(x),
+            _ => None,
+        })
+    }
+
+    pub fn into_symmetric_difference(self, other: Self) -> Self {
+        self.into_merged(other, |merged| match merged {
+            Merged::Left(x) | Merged::Right(x) => Some(x),
+            Merged::Both(..) => None,
+        })
+    }
+
+    fn into_merged<F>(mut self, mut other: Self, pick: F) -> Self
+    where
+        F: FnMut(Merged<T>) -> Option<T>,
+    {
+        self.merge_from(&mut other, pick);
+        self
+    }
+
+    // append在两边的范围有重叠时使用，两边都有的元素保留other中的
+    pub(super) fn append_overlapping(&mut self, other: &mut Self) {
+        self.merge_from(other, |merged| match merged {
+            Merged::Left(x) | Merged::Right(x) | Merged::Both(_, x) => Some(x),
+        });
+    }
+
+    // 归并两棵树的元素，得到的有序序列直接构造成平衡的树，不需要逐个插入
+    fn merge_from<F>(&mut self, other: &mut Self, pick: F)
+    where
+        F: FnMut(Merged<T>) -> Option<T>,
+    {
+        // 只取走self的节点，比较器留在self中，之后用来存放结果
+        let left = IntoIter::new(self.root.take(), std::mem::take(&mut self.size));
+        let right = IntoIter::new(other.root.take(), std::mem::take(&mut other.size));
+        let values: Vec<T> = MergeIter::new(left, right, &self.cmp)
+            .filter_map(pick)
+            .collect();
+        let len = values.len();
+        self.fill_sorted(values.into_iter(), len);
+    }
+}
+
+/// 并集的迭代器，由`RbTree::union`创建
+pub struct Union<'a, T, A: Augment<T> = (), C = NaturalOrder> {
+    merge: MergeIter<'a, T, Iter<'a, T, A>, C>,
+}
+
+impl<'a, T, A: Augment<T>, C: Comparator<T>> Iterator for Union<'a, T, A, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.merge.next()? {
+            Merged::Left(x) | Merged::Right(x) | Merged::Both(x, _) => Some(x),
+        }
+    }
+}
+
+impl<T, A: Augment<T>, C: Comparator<T>> FusedIterator for Union<'_, T, A, C> {}
+
+/// 交集的迭代器，由`RbTree::intersection`创建
+pub struct Intersection<'a, T, A: Augment<T> = (), C = NaturalOrder> {
+    merge: MergeIter<'a, T, Iter<'a, T, A>, C>,
+}
+
+impl<'a, T, A: Augment<T>, C: Comparator<T>> Iterator for Intersection<'a, T, A, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.merge.left_done() && !self.merge.right_done() {
+            if let Some(Merged::Both(x, _)) = self.merge.next() {
+                return Some(x);
+            }
+        }
+        None
+    }
+}
+
+impl<T, A: Augment<T>, C: Comparator<T>> FusedIterator for Intersection<'_, T, A, C> {}
+
+/// 差集的迭代器，由`RbTree::difference`创建
+pub struct Difference<'a, T, A: Augment<T> = (), C = NaturalOrder> {
+    merge: MergeIter<'a, T, Iter<'a, T, A>, C>,
+}
+
+impl<'a, T, A: Augment<T>, C: Comparator<T>> Iterator for Difference<'a, T, A, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // self遍历完后other剩下的元素都不需要再看
+        while !self.merge.left_done() {
+            if let Some(Merged::Left(x)) = self.merge.next() {
+                return Some(x);
+            }
+        }
+        None
+    }
+}
+
+impl<T, A: Augment<T>, C: Comparator<T>> FusedIterator for Difference<'_, T, A, C> {}
+
+/// 对称差的迭代器，由`RbTree::symmetric_difference`创建
+pub struct SymmetricDifference<'a, T, A: Augment<T> = (), C = NaturalOrder> {
+    merge: MergeIter<'a, T, Iter<'a, T, A>, C>,
+}
+
+impl<'a, T, A: Augment<T>, C: Comparator<T>> Iterator for SymmetricDifference<'a, T, A, C> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.merge.next()? {
+                Merged::Left(x) | Merged::Right(x) => return Some(x),
+                Merged::Both(..) => {}
+            }
+        }
+    }
+}
+
+impl<T, A: Augment<T>, C: Comparator<T>> FusedIterator for SymmetricDifference<'_, T, A, C> {}
@@ -0,0 +1,1343 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use proptest::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use super::{
+    Augment, Entry, IntervalTree, IntoIter, InvariantError, RbColor, RbNode, RbTree, RbTreeMap,
+    SubtreeSize,
+};
+
+// 随机测试的操作次数，Miri解释执行很慢，在Miri下按比例缩小
+fn steps(n: usize) -> usize {
+    if cfg!(miri) { n / 20 } else { n }
+}
+
+// 检查以node为根的子树：父指针、连续红节点和每条路径的黑高，返回子树的黑高
+fn check_subtree(
+    node: Option<NonNull<RbNode<i32, ()>>>,
+    parent: Option<NonNull<RbNode<i32, ()>>>,
+) -> usize {
+    let Some(node) = node else {
+        return 0;
+    };
+    let node_ptr = node.as_ptr();
+    unsafe {
+        assert!((*node_ptr).parent == parent, "parent pointer is stale");
+        if (*node_ptr).color == RbColor::Red {
+            assert!(
+                parent.is_none_or(|p| (*p.as_ptr()).color == RbColor::Black),
+                "red node has a red parent"
+            );
+        }
+        let left = check_subtree((*node_ptr).left, Some(node));
+        let right = check_subtree((*node_ptr).right, Some(node));
+        assert_eq!(left, right, "black heights differ");
+        left + usize::from((*node_ptr).color == RbColor::Black)
+    }
+}
+
+fn check_tree(tree: &RbTree<i32>) {
+    if let Some(root) = tree.root {
+        assert!(
+            unsafe { (*root.as_ptr()).color } == RbColor::Black,
+            "root is red"
+        );
+    }
+    check_subtree(tree.root, None);
+}
+
+#[test]
+fn random_updates_keep_parent_pointers_and_black_heights() {
+    for seed in 0..steps(20) as u64 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tree = RbTree::new();
+        let mut set = BTreeSet::new();
+        for _ in 0..2000 {
+            let k = rng.random_range(0..300);
+            if rng.random_bool(0.6) {
+                if set.insert(k) {
+                    tree.insert(k);
+                }
+            } else if set.remove(&k) {
+                tree.delete(&k);
+            }
+            check_tree(&tree);
+        }
+        assert!(tree.enumerate().into_iter().eq(set.iter()));
+    }
+}
+
+#[test]
+fn map_operations_match_btreemap() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut map = RbTreeMap::new();
+    let mut expected = BTreeMap::new();
+    for i in 0..steps(3000) {
+        let k = rng.random_range(0..200);
+        match rng.random_range(0..4) {
+            0 | 1 => assert_eq!(map.insert(k, i), expected.insert(k, i)),
+            2 => {
+                if let Some(v) = map.get_mut(&k) {
+                    *v += 1;
+                }
+                if let Some(v) = expected.get_mut(&k) {
+                    *v += 1;
+                }
+            }
+            _ => assert_eq!(map.remove(&k), expected.remove_entry(&k)),
+        }
+        assert_eq!(map.contains_key(&k), expected.contains_key(&k));
+        assert_eq!(map.get(&k), expected.get(&k));
+    }
+    for k in 0..200 {
+        assert_eq!(map.get(&k), expected.get(&k));
+    }
+}
+
+// 用相同的随机数据构造RbTree和BTreeSet
+fn random_pair(seed: u64, n: usize, max: i32) -> (RbTree<i32>, BTreeSet<i32>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut tree = RbTree::new();
+    let mut set = BTreeSet::new();
+    for _ in 0..n {
+        let k = rng.random_range(0..max);
+        if set.insert(k) {
+            tree.insert(k);
+        }
+    }
+    (tree, set)
+}
+
+#[test]
+fn bound_searches_match_btreeset_range() {
+    for seed in 0..steps(20) as u64 {
+        let (tree, set) = random_pair(seed, 200, 500);
+        for x in -5..505 {
+            assert_eq!(tree.ceiling(&x), set.range(x..).next());
+            assert_eq!(tree.floor(&x), set.range(..=x).next_back());
+            assert_eq!(tree.upper_bound(&x), set.range(x + 1..).next());
+            assert_eq!(tree.lower_bound(&x), set.range(..x).next_back());
+        }
+    }
+}
+
+#[test]
+fn bound_searches_on_empty_tree() {
+    let tree: RbTree<i32> = RbTree::new();
+    assert_eq!(tree.ceiling(&0), None);
+    assert_eq!(tree.floor(&0), None);
+    assert_eq!(tree.upper_bound(&0), None);
+    assert_eq!(tree.lower_bound(&0), None);
+}
+
+#[test]
+fn select_and_rank_track_inserts_and_deletes() {
+    let mut rng = StdRng::seed_from_u64(6);
+    let mut tree = RbTree::with_augment(SubtreeSize);
+    let mut set = BTreeSet::new();
+    for _ in 0..steps(2000) {
+        let k = rng.random_range(0..300);
+        if rng.random_bool(0.6) {
+            if set.insert(k) {
+                tree.insert(k);
+            }
+        } else if set.remove(&k) {
+            tree.delete(&k);
+        }
+    }
+    for (i, v) in set.iter().enumerate() {
+        assert_eq!(tree.select(i), Some(v));
+    }
+    assert_eq!(tree.select(set.len()), None);
+    for x in -1..301 {
+        assert_eq!(tree.rank(&x), set.range(..x).count());
+    }
+}
+
+// 同时维护子树的和与最大值
+struct SumMax;
+
+impl Augment<i64> for SumMax {
+    type Summary = (i64, i64);
+
+    fn combine(left: Option<&(i64, i64)>, value: &i64, right: Option<&(i64, i64)>) -> (i64, i64) {
+        let mut summary = (*value, *value);
+        for &(sum, max) in left.into_iter().chain(right) {
+            summary.0 += sum;
+            summary.1 = summary.1.max(max);
+        }
+        summary
+    }
+}
+
+#[test]
+fn fold_range_matches_linear_scan() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut tree = RbTree::with_augment(SumMax);
+    let mut set = BTreeSet::new();
+    for _ in 0..steps(1000) {
+        let k = rng.random_range(0..200);
+        if rng.random_bool(0.7) {
+            if set.insert(k) {
+                tree.insert(k);
+            }
+        } else if set.remove(&k) {
+            tree.delete(&k);
+        }
+    }
+    for _ in 0..steps(500) {
+        let a = rng.random_range(-10..210);
+        let b = rng.random_range(a..=210);
+        let expected = set
+            .range(a..b)
+            .fold(None, |acc: Option<(i64, i64)>, &v| match acc {
+                Some((sum, max)) => Some((sum + v, max.max(v))),
+                None => Some((v, v)),
+            });
+        assert_eq!(tree.fold_range(a..b), expected);
+    }
+    assert_eq!(tree.fold_range(..).as_ref(), tree.summary());
+}
+
+// 没有实现Clone的摘要：子树中按顺序排列的所有元素
+struct Collected(Vec<i32>);
+
+struct Collect;
+
+impl Augment<i32> for Collect {
+    type Summary = Collected;
+
+    fn combine(left: Option<&Collected>, value: &i32, right: Option<&Collected>) -> Collected {
+        let mut values = left.map_or_else(Vec::new, |l| l.0.to_vec());
+        values.push(*value);
+        values.extend(right.into_iter().flat_map(|r| r.0.iter()));
+        Collected(values)
+    }
+}
+
+#[test]
+fn fold_range_works_without_clone_summaries() {
+    let mut tree = RbTree::with_augment(Collect);
+    for k in [5, 1, 9, 3, 7, 2, 8] {
+        tree.insert(k);
+    }
+    assert_eq!(tree.summary().unwrap().0, [1, 2, 3, 5, 7, 8, 9]);
+    assert_eq!(tree.fold_range(2..8).unwrap().0, [2, 3, 5, 7]);
+    assert!(tree.fold_range(10..).is_none());
+}
+
+#[test]
+fn interval_queries_match_brute_force() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let mut tree = IntervalTree::new();
+    let mut all: Vec<(i32, i32, usize)> = Vec::new();
+    for id in 0..steps(1500) {
+        if rng.random_bool(0.7) || all.is_empty() {
+            // 起点集中在少数几个值上，制造大量起点相同的区间
+            let start = rng.random_range(0..50) * 4;
+            let end = start + rng.random_range(0..40);
+            tree.insert(start..=end, id);
+            all.push((start, end, id));
+        } else if rng.random_bool(0.5) {
+            let i = rng.random_range(0..all.len());
+            let (start, end, id) = all[i];
+            assert_eq!(tree.remove_where(&(start..=end), |&v| v == id), Some(id));
+            all.remove(i);
+        } else {
+            // all按插入顺序排列，remove删除的应是相同区间中最早插入的一个
+            let (start, end, _) = all[rng.random_range(0..all.len())];
+            let pos = all
+                .iter()
+                .position(|&(s, e, _)| s == start && e == end)
+                .unwrap();
+            assert_eq!(tree.remove(&(start..=end)), Some(all.remove(pos).2));
+        }
+    }
+
+    for _ in 0..steps(300) {
+        let a = rng.random_range(-10..250);
+        let b = rng.random_range(a..260);
+        let mut got: Vec<usize> = tree.overlapping(&(a..=b)).map(|(_, &id)| id).collect();
+        let mut expected: Vec<usize> = all
+            .iter()
+            .filter(|&&(s, e, _)| s <= b && e >= a)
+            .map(|&(_, _, id)| id)
+            .collect();
+        got.sort();
+        expected.sort();
+        assert_eq!(got, expected);
+
+        let stabbed = all.iter().filter(|&&(s, e, _)| s <= a && a <= e).count();
+        assert_eq!(tree.containing(&a).count(), stabbed);
+        match tree.any_containing(&a) {
+            Some((range, _)) => assert!(range.contains(&a)),
+            None => assert_eq!(stabbed, 0),
+        }
+    }
+}
+
+#[test]
+fn interval_reversed_query_overlaps_nothing() {
+    let mut tree = IntervalTree::new();
+    tree.insert(0..=10, 'a');
+    tree.insert(4..=4, 'b');
+    tree.insert(6..=20, 'c');
+    // 起点大于终点的查询是空区间
+    for (start, end) in [(5, 3), (10, 0), (i32::MAX, i32::MIN)] {
+        assert_eq!(tree.overlapping(&(start..=end)).count(), 0);
+    }
+    let single: Vec<_> = tree.overlapping(&(5..=5)).map(|(_, &v)| v).collect();
+    assert_eq!(single, ['a']);
+}
+
+#[test]
+fn interval_remove_picks_exact_interval_among_shared_starts() {
+    let mut tree = IntervalTree::new();
+    tree.insert(1..=5, "a");
+    tree.insert(1..=3, "b");
+    tree.insert(1..=9, "c");
+    assert_eq!(tree.remove(&(1..=4)), None);
+    assert_eq!(tree.remove(&(1..=3)), Some("b"));
+    let left: Vec<_> = tree.containing(&2).map(|(_, &v)| v).collect();
+    assert_eq!(left, ["a", "c"]);
+}
+
+#[test]
+fn interval_remove_among_identical_intervals() {
+    let mut tree = IntervalTree::new();
+    for job in ["x", "y", "z", "w"] {
+        tree.insert(2..=6, job);
+    }
+    tree.insert(2..=7, "other");
+    // 指定要删除的值，不存在时什么也不删
+    assert_eq!(tree.remove_where(&(2..=6), |&v| v == "z"), Some("z"));
+    assert_eq!(tree.remove_where(&(2..=6), |&v| v == "z"), None);
+    assert_eq!(tree.remove_where(&(2..=6), |&v| v == "other"), None);
+    // 不指定时按插入的先后删除
+    assert_eq!(tree.remove(&(2..=6)), Some("x"));
+    assert_eq!(tree.remove(&(2..=6)), Some("y"));
+    let left: Vec<_> = tree.containing(&4).map(|(_, &v)| v).collect();
+    assert_eq!(left, ["w", "other"]);
+}
+
+#[test]
+fn custom_comparator_orders_every_search() {
+    let mut tree = RbTree::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    for k in [5, 1, 9, 3, 7] {
+        tree.insert(k);
+    }
+    assert_eq!(tree.iter().copied().collect::<Vec<_>>(), [9, 7, 5, 3, 1]);
+    // 区间的起点和终点也按比较器的顺序给出
+    assert_eq!(
+        tree.range((Bound::Included(8), Bound::Included(3)))
+            .copied()
+            .collect::<Vec<_>>(),
+        [7, 5, 3]
+    );
+    assert_eq!(tree.ceiling(&6), Some(&5));
+    assert_eq!(tree.floor(&6), Some(&7));
+    assert_eq!(tree.get(&3), Some(&3));
+    tree.delete(&9);
+    assert_eq!(tree.iter().next(), Some(&7));
+
+    // 忽略大小写的字符串树，查找时大小写不同的键视为相等
+    let mut words =
+        RbTree::with_comparator(|a: &String, b: &String| a.to_lowercase().cmp(&b.to_lowercase()));
+    for w in ["banana", "Apple", "cherry"] {
+        words.insert(w.to_string());
+    }
+    assert_eq!(words.get(&"APPLE".to_string()), Some(&"Apple".to_string()));
+    assert_eq!(
+        words.iter().map(String::as_str).collect::<Vec<_>>(),
+        ["Apple", "banana", "cherry"]
+    );
+}
+
+#[test]
+fn comparator_works_with_augment() {
+    let mut tree = RbTree::with_augment_and_comparator(SubtreeSize, |a: &i32, b: &i32| b.cmp(a));
+    for k in 0..100 {
+        tree.insert(k);
+    }
+    assert_eq!(tree.select(0), Some(&99));
+    assert_eq!(tree.rank(&90), 9);
+}
+
+#[test]
+fn lookup_by_borrowed_key() {
+    let mut tree = RbTree::new();
+    for w in ["pear", "fig", "kiwi", "lime"] {
+        tree.insert(w.to_string());
+    }
+    assert_eq!(tree.get("fig").map(String::as_str), Some("fig"));
+    assert!(tree.contains("kiwi"));
+    assert!(!tree.contains("plum"));
+    let middle: Vec<_> = tree
+        .range::<str, _>((Bound::Included("g"), Bound::Excluded("m")))
+        .collect();
+    assert_eq!(middle, ["kiwi", "lime"]);
+    tree.delete("kiwi");
+    assert!(!tree.contains("kiwi"));
+
+    let mut map = RbTreeMap::new();
+    map.insert("one".to_string(), 1);
+    map.insert("two".to_string(), 2);
+    *map.get_mut("one").unwrap() += 10;
+    assert_eq!(map.get("one"), Some(&11));
+    assert!(map.contains_key("two"));
+    assert_eq!(map.remove_entry("two"), Some(("two".to_string(), 2)));
+    assert_eq!(map.remove("one"), Some(("one".to_string(), 11)));
+    assert!(!map.contains_key("two"));
+}
+
+#[test]
+fn entry_counts_match_btreemap() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let mut map = RbTreeMap::new();
+    let mut expected = BTreeMap::new();
+    for _ in 0..steps(5000) {
+        let k = rng.random_range(0..400);
+        if rng.random_bool(0.85) {
+            map.entry(k).and_modify(|c| *c += 1).or_insert(1);
+            *expected.entry(k).or_insert(0) += 1;
+        } else if let Entry::Occupied(entry) = map.entry(k) {
+            assert_eq!(Some(entry.remove()), expected.remove(&k));
+        } else {
+            assert!(!expected.contains_key(&k));
+        }
+    }
+    let got: Vec<_> = map.range(..).map(|(&k, &c)| (k, c)).collect();
+    let want: Vec<_> = expected.into_iter().collect();
+    assert_eq!(got, want);
+}
+
+#[test]
+fn entry_inserts_only_when_vacant() {
+    let mut map = RbTreeMap::new();
+    let mut calls = 0;
+    for _ in 0..3 {
+        map.entry("a").or_insert_with(|| {
+            calls += 1;
+            Vec::new()
+        });
+    }
+    assert_eq!(calls, 1);
+    map.entry("a").or_default().push(1);
+    match map.entry("b") {
+        Entry::Vacant(entry) => {
+            assert_eq!(entry.key(), &"b");
+            entry.insert(vec![2]);
+        }
+        Entry::Occupied(_) => unreachable!(),
+    }
+    assert_eq!(map.insert("b", vec![3]), Some(vec![2]));
+    assert_eq!(map.get("a"), Some(&vec![1]));
+}
+
+// 只按第一个分量比较，第二个分量记录插入顺序
+fn by_key(a: &(i32, usize), b: &(i32, usize)) -> std::cmp::Ordering {
+    a.0.cmp(&b.0)
+}
+
+#[test]
+fn set_insert_keeps_first_and_replace_swaps() {
+    let mut tree = RbTree::with_comparator(by_key);
+    assert!(tree.insert((1, 0)));
+    assert!(tree.insert((2, 1)));
+    assert!(!tree.insert((1, 2)));
+    assert_eq!(tree.get(&(1, 99)), Some(&(1, 0)));
+    assert_eq!(tree.count(&(1, 99)), 1);
+
+    assert_eq!(tree.replace((1, 3)), Some((1, 0)));
+    assert_eq!(tree.replace((5, 4)), None);
+    let all: Vec<_> = tree.iter().copied().collect();
+    assert_eq!(all, [(1, 3), (2, 1), (5, 4)]);
+}
+
+#[test]
+fn multiset_keeps_equal_keys_in_insertion_order() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let mut tree = RbTree::with_comparator(by_key);
+    let mut expected: Vec<(i32, usize)> = Vec::new();
+    for id in 0..steps(3000) {
+        let k = rng.random_range(0..40);
+        if rng.random_bool(0.75) {
+            tree.insert_multi((k, id));
+            expected.push((k, id));
+        } else {
+            // remove_one删除的是相等元素中最早插入的一个
+            let first = expected.iter().position(|e| e.0 == k);
+            let removed = tree.remove_one(&(k, 0));
+            assert_eq!(removed, first.map(|pos| expected.remove(pos)));
+        }
+    }
+    // 稳定排序后相等的键保持插入顺序
+    expected.sort_by_key(|e| e.0);
+    let all: Vec<_> = tree.iter().copied().collect();
+    assert_eq!(all, expected);
+
+    for k in 0..40 {
+        let same: Vec<_> = expected.iter().filter(|e| e.0 == k).copied().collect();
+        let got: Vec<_> = tree.equal_range(&(k, 0)).copied().collect();
+        assert_eq!(got, same);
+        assert_eq!(tree.count(&(k, 0)), same.len());
+        assert_eq!(tree.get(&(k, 0)), same.first());
+    }
+
+    assert_eq!(
+        tree.remove_all(&(7, 0)),
+        expected.iter().filter(|e| e.0 == 7).count()
+    );
+    assert_eq!(tree.count(&(7, 0)), 0);
+    assert_eq!(tree.remove_all(&(7, 0)), 0);
+    expected.retain(|e| e.0 != 7);
+    assert!(tree.iter().copied().eq(expected.iter().copied()));
+}
+
+#[test]
+fn drop_and_clear_free_every_node() {
+    // 树中存放Rc的克隆，引用计数回到1说明节点中的值都被释放了
+    let values: Vec<Rc<i32>> = (0..steps(1000) as i32).map(Rc::new).collect();
+    let mut tree = RbTree::new();
+    for v in &values {
+        tree.insert_multi(Rc::clone(v));
+        tree.insert_multi(Rc::clone(v));
+    }
+    assert!(values.iter().all(|v| Rc::strong_count(v) == 3));
+    tree.clear();
+    assert!(values.iter().all(|v| Rc::strong_count(v) == 1));
+    assert_eq!(tree.iter().count(), 0);
+
+    // 清空后的树可以继续使用
+    for v in &values {
+        tree.insert(Rc::clone(v));
+    }
+    assert_eq!(tree.iter().count(), values.len());
+    drop(tree);
+    assert!(values.iter().all(|v| Rc::strong_count(v) == 1));
+
+    let mut map = RbTreeMap::new();
+    for v in &values {
+        map.insert(**v, Rc::clone(v));
+    }
+    drop(map);
+    assert!(values.iter().all(|v| Rc::strong_count(v) == 1));
+}
+
+#[test]
+fn len_tracks_every_kind_of_update() {
+    let mut tree = RbTree::new();
+    assert!(tree.is_empty());
+    assert!(tree.insert(1));
+    assert!(!tree.insert(1));
+    assert_eq!(tree.replace(1), Some(1));
+    assert_eq!(tree.len(), 1);
+    tree.insert_multi(1);
+    tree.insert_multi(2);
+    assert_eq!(tree.len(), 3);
+    assert!(tree.delete(&1));
+    assert!(!tree.delete(&9));
+    assert_eq!(tree.len(), 2);
+    assert_eq!(tree.remove_one(&2), Some(2));
+    assert_eq!(tree.remove_one(&2), None);
+    assert_eq!(tree.len(), 1);
+    for _ in 0..5 {
+        tree.insert_multi(4);
+    }
+    assert_eq!(tree.remove_all(&4), 5);
+    assert_eq!(tree.len(), 1);
+    tree.clear();
+    assert!(tree.is_empty());
+
+    let (tree, set) = random_pair(14, 500, 300);
+    assert_eq!(tree.len(), set.len());
+    assert_eq!(tree.iter().len(), set.len());
+
+    let mut map = RbTreeMap::new();
+    map.entry(1).or_insert(0);
+    map.insert(1, 5);
+    map.insert(2, 6);
+    assert_eq!(map.len(), 2);
+    if let Entry::Occupied(entry) = map.entry(2) {
+        entry.remove();
+    }
+    map.remove(&1);
+    assert!(map.is_empty());
+
+    let mut intervals = IntervalTree::new();
+    intervals.insert(0..=3, ());
+    intervals.insert(0..=3, ());
+    assert_eq!(intervals.len(), 2);
+    intervals.remove(&(0..=3));
+    assert_eq!(intervals.len(), 1);
+}
+
+#[test]
+fn remove_hands_back_the_owned_value() {
+    // 按优先级排序的任务，删除时取回完整的任务
+    let mut pending = RbTree::with_comparator(by_key);
+    for (id, priority) in [3, 1, 2].into_iter().enumerate() {
+        pending.insert((priority, id));
+    }
+    let mut running = Vec::new();
+    running.extend(pending.remove(&(1, 0)));
+    running.extend(pending.take(&(3, 0)));
+    assert_eq!(pending.remove(&(1, 0)), None);
+    assert_eq!(running, [(1, 1), (3, 0)]);
+    assert_eq!(pending.len(), 1);
+
+    let mut map = RbTreeMap::new();
+    map.insert("task".to_string(), vec![1, 2]);
+    assert_eq!(
+        map.remove_entry("task"),
+        Some(("task".to_string(), vec![1, 2]))
+    );
+    assert_eq!(map.remove("task"), None);
+}
+
+#[test]
+fn double_ended_priority_queue_matches_btreeset() {
+    let mut rng = StdRng::seed_from_u64(16);
+    let (mut tree, mut set) = random_pair(16, 300, 1000);
+    for _ in 0..steps(3000) {
+        match rng.random_range(0..4) {
+            0 => assert_eq!(tree.pop_first(), set.pop_first()),
+            1 => assert_eq!(tree.pop_last(), set.pop_last()),
+            _ => {
+                let k = rng.random_range(0..1000);
+                assert_eq!(tree.insert(k), set.insert(k));
+            }
+        }
+        assert_eq!(tree.first(), set.first());
+        assert_eq!(tree.last(), set.last());
+        assert_eq!(tree.len(), set.len());
+    }
+    while let Some(k) = set.pop_first() {
+        assert_eq!(tree.pop_first(), Some(k));
+    }
+    assert_eq!(tree.pop_last(), None);
+    assert_eq!(tree.first(), None);
+}
+
+#[test]
+fn random_updates_keep_invariants() {
+    let mut rng = StdRng::seed_from_u64(17);
+    let mut tree = RbTree::new();
+    for _ in 0..steps(3000) {
+        let k = rng.random_range(0..200);
+        match rng.random_range(0..5) {
+            0 => tree.insert_multi(k),
+            1 | 2 => {
+                tree.insert(k);
+            }
+            3 => {
+                tree.remove(&k);
+            }
+            _ => {
+                tree.pop_first();
+            }
+        }
+        assert_eq!(tree.validate(), Ok(()));
+    }
+}
+
+// 1 2 3 三个节点，2是黑色的根，1和3是红色的叶子
+fn three_nodes() -> RbTree<i32> {
+    let mut tree = RbTree::new();
+    for k in [2, 1, 3] {
+        tree.insert(k);
+    }
+    tree.check_invariants();
+    tree
+}
+
+fn node_of(tree: &RbTree<i32>, key: i32) -> *mut RbNode<i32, ()> {
+    tree.find_by(|v| key.cmp(v)).unwrap().as_ptr()
+}
+
+#[test]
+fn validate_reports_each_kind_of_violation() {
+    let tree = three_nodes();
+    unsafe { (*node_of(&tree, 2)).color = RbColor::Red };
+    assert_eq!(tree.validate(), Err(InvariantError::RedRoot));
+
+    let mut tree = three_nodes();
+    tree.insert(4);
+    // 插入4后1和3变黑，4是3的红色右子节点，把1和3都改成红色，黑高仍然相同
+    unsafe {
+        (*node_of(&tree, 1)).color = RbColor::Red;
+        (*node_of(&tree, 3)).color = RbColor::Red;
+    }
+    assert_eq!(tree.validate(), Err(InvariantError::RedRed { index: 3 }));
+
+    let tree = three_nodes();
+    unsafe { (*node_of(&tree, 1)).color = RbColor::Black };
+    assert_eq!(
+        tree.validate(),
+        Err(InvariantError::BlackHeight {
+            expected: 2,
+            found: 1
+        })
+    );
+
+    let tree = three_nodes();
+    unsafe { (*node_of(&tree, 3)).parent = Some(NonNull::new(node_of(&tree, 1)).unwrap()) };
+    assert_eq!(
+        tree.validate(),
+        Err(InvariantError::ParentPointer { index: 2 })
+    );
+
+    let tree = three_nodes();
+    unsafe {
+        let (one, three) = (node_of(&tree, 1), node_of(&tree, 3));
+        std::mem::swap(&mut (*one).value, &mut (*three).value);
+    }
+    assert_eq!(tree.validate(), Err(InvariantError::Order { index: 1 }));
+
+    let mut tree = three_nodes();
+    tree.size += 1;
+    assert_eq!(
+        tree.validate(),
+        Err(InvariantError::Size { len: 4, nodes: 3 })
+    );
+    tree.size -= 1;
+}
+
+// 差分测试：同一串操作同时作用在RbTree和BTreeSet上，每一步之后比较两者
+// 失败时proptest会不断删减操作、缩小键值，给出最小的失败序列
+#[derive(Debug, Clone)]
+enum Op {
+    Insert(i32),
+    Delete(i32),
+    Get(i32),
+}
+
+fn op_strategy() -> impl Strategy<Value = Op> {
+    // 键的范围较小，删除和查找经常能命中已有的键
+    prop_oneof![
+        3 => (0..64).prop_map(Op::Insert),
+        2 => (0..64).prop_map(Op::Delete),
+        1 => (0..64).prop_map(Op::Get),
+    ]
+}
+
+fn run_ops(ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut tree = RbTree::new();
+    let mut set = BTreeSet::new();
+    for op in ops {
+        match *op {
+            Op::Insert(k) => prop_assert_eq!(tree.insert(k), set.insert(k)),
+            Op::Delete(k) => prop_assert_eq!(tree.delete(&k), set.remove(&k)),
+            Op::Get(k) => prop_assert_eq!(tree.get(&k), set.get(&k)),
+        }
+        prop_assert_eq!(tree.validate(), Ok(()));
+        prop_assert_eq!(tree.len(), set.len());
+        prop_assert!(tree.enumerate().into_iter().eq(set.iter()));
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: steps(512) as u32,
+        // Miri默认隔离文件系统，不保存失败的用例
+        failure_persistence: if cfg!(miri) {
+            None
+        } else {
+            ProptestConfig::default().failure_persistence
+        },
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    fn random_ops_match_btreeset(ops in prop::collection::vec(op_strategy(), 0..300)) {
+        run_ops(&ops)?;
+    }
+}
+
+// main.rs中固定种子生成的序列，作为回归输入
+#[test]
+fn main_sequences_as_regression_inputs() {
+    let mut rng = StdRng::seed_from_u64(0x12345678);
+    let mut keys: Vec<i32> = (0..200).collect();
+    keys.shuffle(&mut rng);
+    let mut ops: Vec<Op> = keys.iter().map(|&k| Op::Insert(k)).collect();
+    ops.extend(keys.iter().map(|&k| Op::Get(k)));
+    keys.shuffle(&mut rng);
+    ops.extend(keys.iter().map(|&k| Op::Delete(k)));
+    run_ops(&ops).unwrap();
+
+    let keys = [1, 9, 2, 8, 3, 7, 4, 6, 5];
+    let mut ops: Vec<Op> = keys.iter().map(|&k| Op::Insert(k)).collect();
+    ops.extend(keys.iter().map(|&k| Op::Delete(k)));
+    run_ops(&ops).unwrap();
+}
+
+fn copy_of(tree: &RbTree<i32>) -> RbTree<i32> {
+    let mut copy = RbTree::new();
+    for &k in tree {
+        copy.insert(k);
+    }
+    copy
+}
+
+#[test]
+fn set_operations_match_btreeset() {
+    for seed in 0..steps(40) as u64 {
+        let (a, sa) = random_pair(seed, 150, 300);
+        let (b, sb) = random_pair(seed + 1000, seed as usize * 10, 300);
+        assert!(a.union(&b).eq(sa.union(&sb)));
+        assert!(a.intersection(&b).eq(sa.intersection(&sb)));
+        assert!(a.difference(&b).eq(sa.difference(&sb)));
+        assert!(a.symmetric_difference(&b).eq(sa.symmetric_difference(&sb)));
+        assert_eq!(a.is_subset(&b), sa.is_subset(&sb));
+        assert_eq!(b.is_subset(&a), sb.is_subset(&sa));
+        assert_eq!(a.is_superset(&b), sa.is_superset(&sb));
+        assert_eq!(a.is_disjoint(&b), sa.is_disjoint(&sb));
+
+        let results = [
+            (copy_of(&a).into_union(copy_of(&b)), &sa | &sb),
+            (copy_of(&a).into_intersection(copy_of(&b)), &sa & &sb),
+            (copy_of(&a).into_difference(copy_of(&b)), &sa - &sb),
+            (
+                copy_of(&a).into_symmetric_difference(copy_of(&b)),
+                &sa ^ &sb,
+            ),
+        ];
+        for (tree, expected) in results {
+            assert_eq!(tree.validate(), Ok(()));
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.iter().eq(expected.iter()));
+        }
+    }
+}
+
+#[test]
+fn subset_predicates_on_small_sets() {
+    let mut small = RbTree::new();
+    small.insert(2);
+    small.insert(4);
+    let mut big = RbTree::new();
+    for k in 0..10 {
+        big.insert(k);
+    }
+    let empty = RbTree::new();
+    assert!(small.is_subset(&big));
+    assert!(big.is_superset(&small));
+    assert!(!big.is_subset(&small));
+    assert!(empty.is_subset(&small));
+    assert!(empty.is_disjoint(&big));
+    assert!(!small.is_disjoint(&big));
+}
+
+#[test]
+fn split_off_and_append_match_btreeset() {
+    for seed in 0..steps(60) as u64 {
+        let (mut tree, mut set) = random_pair(seed, seed as usize * 8, 400);
+        let key = StdRng::seed_from_u64(seed).random_range(-10..410);
+        let mut tree_right = tree.split_off(&key);
+        let mut set_right = set.split_off(&key);
+        for (part, expected) in [(&tree, &set), (&tree_right, &set_right)] {
+            assert_eq!(part.validate(), Ok(()));
+            assert_eq!(part.len(), expected.len());
+            assert!(part.iter().eq(expected.iter()));
+        }
+
+        // 不重叠的两棵树，无论哪一边在前都走join
+        if seed % 2 == 0 {
+            tree.append(&mut tree_right);
+        } else {
+            tree_right.append(&mut tree);
+            std::mem::swap(&mut tree, &mut tree_right);
+        }
+        set.append(&mut set_right);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree_right.is_empty() && tree_right.validate() == Ok(()));
+        assert_eq!(tree.len(), set.len());
+        assert!(tree.iter().eq(set.iter()));
+
+        // 范围重叠时退化为归并
+        let (mut other, mut other_set) = random_pair(seed + 1000, 100, 400);
+        tree.append(&mut other);
+        set.append(&mut other_set);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(other.is_empty());
+        assert!(tree.iter().eq(set.iter()));
+    }
+}
+
+#[test]
+fn split_off_with_subtree_size_keeps_lengths_and_ranks() {
+    for seed in 0..steps(60) as u64 {
+        let (plain, set) = random_pair(seed, seed as usize * 8, 400);
+        let mut tree = RbTree::with_augment(SubtreeSize);
+        tree.extend(plain.iter().copied());
+        let key = StdRng::seed_from_u64(seed).random_range(-10..410);
+        let right = tree.split_off(&key);
+        let boundary = set.range(..key).count();
+        assert_eq!((tree.len(), right.len()), (boundary, set.len() - boundary));
+        for part in [&tree, &right] {
+            assert_eq!(part.validate(), Ok(()));
+            assert_eq!(part.summary().copied().unwrap_or(0), part.len());
+            for (i, v) in part.iter().enumerate() {
+                assert_eq!(part.select(i), Some(v));
+                assert_eq!(part.rank(v), i);
+            }
+        }
+    }
+}
+
+#[test]
+fn append_joins_trees_of_very_different_heights() {
+    let mut big = RbTree::with_augment(SubtreeSize);
+    for k in 100..2100 {
+        big.insert(k);
+    }
+    let mut small = RbTree::with_augment(SubtreeSize);
+    small.insert(5);
+    small.insert(7);
+    small.append(&mut big);
+    assert_eq!(small.validate(), Ok(()));
+    assert_eq!(small.len(), 2002);
+    assert_eq!(small.summary(), Some(&2002));
+    assert_eq!(small.select(2), Some(&100));
+
+    let mut tail = RbTree::with_augment(SubtreeSize);
+    tail.insert(5000);
+    small.append(&mut tail);
+    assert_eq!(small.validate(), Ok(()));
+    assert_eq!(small.summary(), Some(&2003));
+    assert_eq!(small.last(), Some(&5000));
+
+    // 拆分后两边的摘要也要正确
+    let right = small.split_off(&1000);
+    assert_eq!(small.validate(), Ok(()));
+    assert_eq!(right.validate(), Ok(()));
+    assert_eq!(small.summary(), Some(&902));
+    assert_eq!(right.summary(), Some(&1101));
+    assert_eq!(right.rank(&1500), 500);
+}
+
+#[test]
+fn from_sorted_iter_builds_valid_trees_of_every_size() {
+    // 覆盖最底层为空、部分填满和恰好填满的各种大小
+    for n in 0..steps(300) {
+        let tree: RbTree<usize> = RbTree::from_sorted_iter(0..n);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.len(), n);
+        assert!(tree.iter().copied().eq(0..n));
+    }
+    let tree: RbTree<i32, SubtreeSize> = RbTree::from_sorted_iter([1, 2, 2, 2, 3]);
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.count(&2), 3);
+    assert_eq!(tree.summary(), Some(&5));
+}
+
+#[test]
+#[should_panic(expected = "not sorted")]
+fn from_sorted_iter_rejects_unsorted_input() {
+    let _: RbTree<i32> = RbTree::from_sorted_iter([1, 3, 2]);
+}
+
+#[test]
+fn collect_extend_and_from_array_use_set_semantics() {
+    let mut rng = StdRng::seed_from_u64(23);
+    let keys: Vec<i32> = (0..steps(2000)).map(|_| rng.random_range(0..500)).collect();
+    let tree: RbTree<i32> = keys.iter().copied().collect();
+    let set: BTreeSet<i32> = keys.iter().copied().collect();
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.len(), set.len());
+    assert!(tree.iter().eq(set.iter()));
+
+    let mut tree: RbTree<i32> = RbTree::from([5, 1, 5, 3]);
+    assert!(tree.iter().copied().eq([1, 3, 5]));
+    tree.extend([2, 3, 4]);
+    tree.extend(&[0, 6]);
+    assert_eq!(tree.validate(), Ok(()));
+    assert!(tree.iter().copied().eq(0..=6));
+}
+
+#[test]
+fn cursor_walks_both_ways_through_the_ghost() {
+    let tree: RbTree<i32> = RbTree::from_sorted_iter([10, 20, 30]);
+    let mut cursor = tree.cursor_front();
+    assert_eq!(cursor.current(), Some(&10));
+    assert_eq!(cursor.peek_prev(), None);
+    cursor.move_prev();
+    assert_eq!(cursor.current(), None);
+    assert_eq!(
+        (cursor.peek_prev(), cursor.peek_next()),
+        (Some(&30), Some(&10))
+    );
+    cursor.move_prev();
+    assert_eq!(cursor.current(), Some(&30));
+
+    let mut cursor = tree.cursor_ceiling(&15);
+    assert_eq!(cursor.current(), Some(&20));
+    assert_eq!(
+        (cursor.peek_prev(), cursor.peek_next()),
+        (Some(&10), Some(&30))
+    );
+    cursor.move_next();
+    cursor.move_next();
+    assert_eq!(cursor.current(), None);
+    assert_eq!(tree.cursor_ceiling(&31).current(), None);
+
+    let empty: RbTree<i32> = RbTree::new();
+    let mut cursor = empty.cursor_back();
+    cursor.move_next();
+    assert_eq!((cursor.current(), cursor.peek_next()), (None, None));
+}
+
+#[test]
+fn cursor_mut_edits_match_vec_model() {
+    let mut rng = StdRng::seed_from_u64(24);
+    let mut tree: RbTree<i32> = RbTree::new();
+    // 模型里额外记录游标的位置，len表示幽灵位置
+    let mut model: Vec<i32> = Vec::new();
+    let mut pos = 0;
+    let mut cursor = tree.cursor_front_mut();
+    for _ in 0..steps(3000) {
+        match rng.random_range(0..5) {
+            0 => {
+                cursor.move_next();
+                pos = if pos == model.len() { 0 } else { pos + 1 };
+            }
+            1 => {
+                cursor.move_prev();
+                pos = if pos == 0 { model.len() } else { pos - 1 };
+            }
+            2 => {
+                let removed = cursor.remove_current();
+                if pos < model.len() {
+                    assert_eq!(removed, Some(model.remove(pos)));
+                } else {
+                    assert_eq!(removed, None);
+                }
+            }
+            op => {
+                // 在相邻元素之间取值，偶尔取一个越界的值检查错误
+                let lo = if pos > 0 { model[pos - 1] } else { -1000 };
+                let hi = model.get(pos).copied().unwrap_or(lo + 1000);
+                let value = if op == 3 {
+                    rng.random_range(lo..=hi)
+                } else {
+                    hi + 1
+                };
+                let result = cursor.insert_before(value);
+                if value <= hi || pos == model.len() {
+                    assert_eq!(result, Ok(()));
+                    model.insert(pos, value);
+                    pos += 1;
+                } else {
+                    assert_eq!(result, Err(super::UnorderedKeyError(value)));
+                }
+            }
+        }
+        assert_eq!(cursor.current(), model.get(pos));
+    }
+    let mut cursor = tree.cursor_front_mut();
+    assert_eq!(
+        cursor.insert_after(i32::MIN),
+        Err(super::UnorderedKeyError(i32::MIN))
+    );
+    cursor.move_prev();
+    assert_eq!(cursor.insert_after(i32::MIN), Ok(()));
+    model.insert(0, i32::MIN);
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.len(), model.len());
+    assert!(tree.iter().eq(model.iter()));
+}
+
+#[test]
+fn retain_and_extract_if_match_btreeset() {
+    for seed in 0..steps(40) as u64 {
+        let (mut tree, mut set) = random_pair(seed, 300, 1000);
+        let modulus = seed as i32 % 5 + 2;
+        tree.retain(|k| k % modulus != 0);
+        set.retain(|k| k % modulus != 0);
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.len(), set.len());
+        assert!(tree.iter().eq(set.iter()));
+
+        let extracted: Vec<i32> = tree.extract_if(|k| k % 3 == 1).collect();
+        let expected: Vec<i32> = set.extract_if(.., |k| k % 3 == 1).collect();
+        assert_eq!(extracted, expected);
+        assert_eq!(tree.validate(), Ok(()));
+        assert!(tree.iter().eq(set.iter()));
+    }
+}
+
+#[test]
+fn extract_if_is_lazy() {
+    let mut tree: RbTree<i32, SubtreeSize> = RbTree::from_sorted_iter(0..100);
+    let mut calls = 0;
+    {
+        let mut evens = tree.extract_if(|k| {
+            calls += 1;
+            k % 2 == 0
+        });
+        assert_eq!(evens.next(), Some(0));
+        assert_eq!(evens.next(), Some(2));
+    }
+    assert_eq!(calls, 3);
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.summary(), Some(&98));
+    assert_eq!(tree.first(), Some(&1));
+    assert!(tree.contains(&4));
+
+    tree.retain(|_| false);
+    assert!(tree.is_empty());
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn iter_from_both_ends_matches_btreeset() {
+    for seed in 0..steps(20) as u64 {
+        let (tree, set) = random_pair(seed, seed as usize * 7, 300);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut iter = tree.iter();
+        let mut expected = set.iter();
+        loop {
+            assert_eq!(iter.len(), expected.len());
+            let (got, want) = if rng.random_bool(0.5) {
+                (iter.next(), expected.next())
+            } else {
+                (iter.next_back(), expected.next_back())
+            };
+            assert_eq!(got, want);
+            if got.is_none() {
+                break;
+            }
+        }
+        // 两端相遇后不会再交叉产生元素
+        assert_eq!((iter.next(), iter.next_back()), (None, None));
+    }
+}
+
+#[test]
+fn into_iter_from_both_ends_matches_btreeset() {
+    for seed in 0..steps(20) as u64 {
+        let (tree, set) = random_pair(seed, seed as usize * 7, 300);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut iter = tree.into_iter();
+        let mut expected = set.into_iter();
+        loop {
+            assert_eq!(iter.len(), expected.len());
+            let (got, want) = if rng.random_bool(0.5) {
+                (iter.next(), expected.next())
+            } else {
+                (iter.next_back(), expected.next_back())
+            };
+            assert_eq!(got, want);
+            if got.is_none() {
+                break;
+            }
+        }
+        assert_eq!((iter.next(), iter.next_back()), (None, None));
+    }
+}
+
+#[test]
+fn into_iter_dropped_early_frees_the_rest() {
+    let values: Vec<Rc<i32>> = (0..steps(1000) as i32).map(Rc::new).collect();
+    for taken in [0, 1, values.len() / 3, values.len()] {
+        let mut tree = RbTree::new();
+        for v in &values {
+            tree.insert(Rc::clone(v));
+        }
+        let mut iter = tree.into_iter();
+        // 两端各取一部分，剩下的留给Drop释放
+        for i in 0..taken {
+            let value = if i % 2 == 0 {
+                iter.next()
+            } else {
+                iter.next_back()
+            };
+            assert!(value.is_some());
+        }
+        assert_eq!(iter.len(), values.len() - taken);
+        drop(iter);
+        assert!(values.iter().all(|v| Rc::strong_count(v) == 1));
+    }
+}
+
+#[test]
+fn into_iter_with_wrong_len_stops_where_the_ends_meet() {
+    let n = steps(200);
+    let values: Vec<Rc<usize>> = (0..n).map(Rc::new).collect();
+    for (seed, len) in [0, 1, n / 2, n - 1, n + 1, 2 * n].into_iter().enumerate() {
+        let mut tree = RbTree::new();
+        for v in &values {
+            tree.insert(Rc::clone(v));
+        }
+        // 长度与实际节点数不一致时，两端也只能在指针相遇时结束
+        let mut iter = IntoIter::new(tree.root.take(), len);
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+        let mut expected: std::collections::VecDeque<_> = values.iter().collect();
+        loop {
+            let (got, want) = if rng.random_bool(0.5) {
+                (iter.next(), expected.pop_front())
+            } else {
+                (iter.next_back(), expected.pop_back())
+            };
+            assert_eq!(got.as_ref(), want);
+            if got.is_none() {
+                break;
+            }
+        }
+        assert_eq!((iter.next(), iter.next_back()), (None, None));
+        assert_eq!(iter.len(), 0);
+        assert!(values.iter().all(|v| Rc::strong_count(v) == 1));
+
+        // 提前丢弃时同样只释放剩下的节点
+        let mut tree = RbTree::new();
+        for v in &values {
+            tree.insert(Rc::clone(v));
+        }
+        let mut iter = IntoIter::new(tree.root.take(), len);
+        iter.next();
+        iter.next_back();
+        drop(iter);
+        assert!(values.iter().all(|v| Rc::strong_count(v) == 1));
+    }
+}
+
+// 随机生成BTreeSet::range能接受的区间：起点不大于终点，相等时两端不同时排除
+fn random_bounds(rng: &mut StdRng, max: i32) -> (Bound<i32>, Bound<i32>) {
+    let a = rng.random_range(-5..max + 5);
+    let b = rng.random_range(a..max + 5);
+    let bound = |rng: &mut StdRng, x: i32| match rng.random_range(0..3) {
+        0 => Bound::Included(x),
+        1 => Bound::Excluded(x),
+        _ => Bound::Unbounded,
+    };
+    let start = bound(rng, a);
+    let end = match (start, bound(rng, b)) {
+        (Bound::Excluded(_), Bound::Excluded(_)) if a == b => Bound::Included(b),
+        (_, end) => end,
+    };
+    (start, end)
+}
+
+#[test]
+fn range_from_both_ends_matches_btreeset() {
+    for seed in 0..steps(200) as u64 {
+        let (tree, set) = random_pair(seed, seed as usize % 150, 200);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let bounds = random_bounds(&mut rng, 200);
+        let mut range = tree.range(bounds);
+        let mut expected = set.range(bounds);
+        loop {
+            let (got, want) = if rng.random_bool(0.5) {
+                (range.next(), expected.next())
+            } else {
+                (range.next_back(), expected.next_back())
+            };
+            assert_eq!(got, want, "{bounds:?}");
+            if got.is_none() {
+                break;
+            }
+        }
+        assert_eq!((range.next(), range.next_back()), (None, None));
+    }
+}
+
+#[test]
+fn map_range_and_range_mut_match_btreemap() {
+    for seed in 0..steps(200) as u64 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut map = RbTreeMap::new();
+        let mut expected = BTreeMap::new();
+        for _ in 0..seed % 150 {
+            let k = rng.random_range(0..200);
+            map.insert(k, k * 10);
+            expected.insert(k, k * 10);
+        }
+
+        let bounds = random_bounds(&mut rng, 200);
+        let mut range = map.range(bounds);
+        let mut want_range = expected.range(bounds);
+        loop {
+            let (got, want) = if rng.random_bool(0.5) {
+                (range.next(), want_range.next())
+            } else {
+                (range.next_back(), want_range.next_back())
+            };
+            assert_eq!(got, want, "{bounds:?}");
+            if got.is_none() {
+                break;
+            }
+        }
+
+        // 两端交替修改，再比较整个映射
+        let bounds = random_bounds(&mut rng, 200);
+        let mut range = map.range_mut(bounds);
+        let mut want_range = expected.range_mut(bounds);
+        let mut step = 0;
+        loop {
+            step += 1;
+            let (got, want) = if rng.random_bool(0.5) {
+                (range.next(), want_range.next())
+            } else {
+                (range.next_back(), want_range.next_back())
+            };
+            match (got, want) {
+                (Some((k, v)), Some((want_k, want_v))) => {
+                    assert_eq!(k, want_k);
+                    *v += step;
+                    *want_v += step;
+                }
+                (got, want) => {
+                    assert!(got.is_none() && want.is_none(), "{bounds:?}");
+                    break;
+                }
+            }
+        }
+        assert!(map.range(..).eq(expected.iter()));
+    }
+}
+
+// 先序写出每个节点的值和颜色，如"5B 2R 1B"
+fn preorder_shape(node: Option<NonNull<RbNode<i32, ()>>>, out: &mut Vec<String>) {
+    let Some(node) = node else {
+        return;
+    };
+    let node_ptr = node.as_ptr();
+    unsafe {
+        let color = if (*node_ptr).color == RbColor::Red {
+            'R'
+        } else {
+            'B'
+        };
+        out.push(format!("{}{color}", (*node_ptr).value));
+        preorder_shape((*node_ptr).left, out);
+        preorder_shape((*node_ptr).right, out);
+    }
+}
+
+// 插入改为迭代实现之前由递归版本得到的形状，两种实现的旋转和变色应完全一致
+#[test]
+fn insert_produces_pinned_tree_shape() {
+    let mut rng = StdRng::seed_from_u64(20);
+    let mut tree = RbTree::new();
+    for _ in 0..48 {
+        tree.insert(rng.random_range(0..100));
+    }
+    for _ in 0..8 {
+        tree.insert_multi(rng.random_range(0..100));
+    }
+    let mut shape = Vec::new();
+    preorder_shape(tree.root, &mut shape);
+    assert_eq!(
+        shape.join(" "),
+        "48B 33B 17B 8R 3B 6R 9B 13R 25R 24B 20R 28B 26R 31R 44B 42R 38B 36R 41R 43B 46B 45R 47R \
+         70B 54B 49B 48R 52R 62R 56B 59R 68B 87R 80B 78R 77B 76R 78B 82B 82R 86R 92B 91B 90R 97B 98R"
+    );
+}
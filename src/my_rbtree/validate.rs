@@ -0,0 +1,162 @@
+use std::{cmp::Ordering, fmt, ptr::NonNull};
+
+use super::{Augment, Comparator, RbColor, RbNode, RbTree};
+
+/// `RbTree::validate`发现的不变式错误，index是出错节点在中序上的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantError {
+    /// 根节点是红色
+    RedRoot,
+    /// 红节点的父节点也是红色，index是子节点的位置
+    RedRed { index: usize },
+    /// 从根到不同空叶子的路径上黑节点数不同
+    BlackHeight { expected: usize, found: usize },
+    /// 子节点的父指针没有指向它的父节点，根节点的父指针不为空也算在内
+    ParentPointer { index: usize },
+    /// 中序上相邻的两个元素顺序颠倒
+    Order { index: usize },
+    /// 记录的元素个数与实际的节点个数不同
+    Size { len: usize, nodes: usize },
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::RedRoot => write!(f, "root is red"),
+            InvariantError::RedRed { index } => {
+                write!(f, "red node at index {index} has a red parent")
+            }
+            InvariantError::BlackHeight { expected, found } => {
+                write!(f, "black height {found} differs from {expected}")
+            }
+            InvariantError::ParentPointer { index } => {
+                write!(f, "parent pointer of node at index {index} is inconsistent")
+            }
+            InvariantError::Order { index } => {
+                write!(f, "node at index {index} is out of order")
+            }
+            InvariantError::Size { len, nodes } => {
+                write!(f, "len is {len} but the tree has {nodes} nodes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvariantError {}
+
+impl<T, A: Augment<T>, C> RbTree<T, A, C> {
+    /// 检查红黑树的所有不变式，返回发现的第一个错误
+    pub fn validate(&self) -> Result<(), InvariantError>
+    where
+        C: Comparator<T>,
+    {
+        self.validate_structure()?;
+        // 结构正确后父指针可信，可以直接用中序遍历检查顺序
+        let mut prev: Option<&T> = None;
+        for (index, value) in self.iter().enumerate() {
+            if let Some(p) = prev
+                && self.cmp.compare(p, value) == Ordering::Greater
+            {
+                return Err(InvariantError::Order { index });
+            }
+            prev = Some(value);
+        }
+        Ok(())
+    }
+
+    /// 与`validate`相同，发现错误时panic
+    pub fn check_invariants(&self)
+    where
+        C: Comparator<T>,
+    {
+        if let Err(err) = self.validate() {
+            panic!("red-black tree invariant violated: {err}");
+        }
+    }
+
+    // 检查除顺序以外的不变式，不需要比较器
+    // 用显式的栈做中序遍历，结构损坏成很深的链时也不会栈溢出
+    fn validate_structure(&self) -> Result<(), InvariantError> {
+        if let Some(root) = self.root
+            && unsafe { (*root.as_ptr()).color } == RbColor::Red
+        {
+            return Err(InvariantError::RedRoot);
+        }
+
+        let mut black_height = None;
+        let mut check_leaf = |blacks: usize| match black_height {
+            None => {
+                black_height = Some(blacks);
+                Ok(())
+            }
+            Some(expected) if expected != blacks => Err(InvariantError::BlackHeight {
+                expected,
+                found: blacks,
+            }),
+            Some(_) => Ok(()),
+        };
+
+        // 栈中记录节点、遍历时经过的父节点和从根到它（包含它）的黑节点数
+        let mut stack = Vec::new();
+        let mut current = self.root.map(|root| (root, None, 1));
+        let mut nodes = 0;
+        let mut index = 0;
+        loop {
+            while let Some((node, parent, blacks)) = current {
+                nodes += 1;
+                // 指针出错可能让节点被访问多次甚至成环，节点数超出后就不必继续
+                if nodes > self.size {
+                    return Err(InvariantError::Size {
+                        len: self.size,
+                        nodes,
+                    });
+                }
+                stack.push((node, parent, blacks));
+                current = unsafe { (*node.as_ptr()).left }
+                    .map(|left| (left, Some(node), blacks + black_count(left)));
+            }
+            let Some((node, parent, blacks)) = stack.pop() else {
+                break;
+            };
+            let node_ptr = node.as_ptr();
+            unsafe {
+                if (*node_ptr).parent != parent {
+                    return Err(InvariantError::ParentPointer { index });
+                }
+                if (*node_ptr).color == RbColor::Red
+                    && parent.is_some_and(|p| (*p.as_ptr()).color == RbColor::Red)
+                {
+                    return Err(InvariantError::RedRed { index });
+                }
+                if (*node_ptr).left.is_none() || (*node_ptr).right.is_none() {
+                    check_leaf(blacks)?;
+                }
+                current = (*node_ptr)
+                    .right
+                    .map(|right| (right, Some(node), blacks + black_count(right)));
+            }
+            index += 1;
+        }
+        if nodes != self.size {
+            return Err(InvariantError::Size {
+                len: self.size,
+                nodes,
+            });
+        }
+        Ok(())
+    }
+
+    // 开启paranoid特性时，每次修改结构后都检查一遍
+    // 顺序的检查需要比较器，留给validate
+    pub(super) fn paranoid_check(&self) {
+        #[cfg(feature = "paranoid")]
+        if let Err(err) = self.validate_structure() {
+            panic!("red-black tree invariant violated: {err}");
+        }
+    }
+}
+
+// 节点本身贡献的黑高
+pub(super) fn black_count<T, A: Augment<T>>(node: NonNull<RbNode<T, A>>) -> usize {
+    usize::from(unsafe { (*node.as_ptr()).color } == RbColor::Black)
+}